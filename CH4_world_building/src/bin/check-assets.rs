@@ -0,0 +1,249 @@
+//! Checks every asset the game refers to: the paths registered in code and the data
+//! files, that each exists and parses, and that the protagonist's glTF has the clips
+//! `SCENES` expects. Prints a JSON report and exits with 1 if anything is broken.
+//!
+//! `cargo run --bin check-assets > asset-report.json`
+
+use bevy_stealth::level::{asset_path, LevelData, LEVEL_PATH};
+use bevy_stealth::resources::{PROTAGONIST_ANIMATIONS, PROTAGONIST_GLB, REGISTERED_ASSETS, SCENES};
+use bevy_stealth::systems::audio::{Ambience, SoundCue};
+use bevy_stealth::systems::blend::{BlendSpacesConfig, BLEND_SPACES_PATH};
+use bevy_stealth::systems::controller::{ControllerConfig, CONTROLLER_CONFIG_PATH};
+use bevy_stealth::systems::markers::{MarkerTime, ANIMATION_EVENTS_PATH};
+use bevy_stealth::systems::objectives::{Objectives, OBJECTIVES_PATH};
+use bevy_stealth::systems::rocks::{RockConfig, ROCKS_CONFIG_PATH};
+use bevy_stealth::systems::root_motion::{RootMotionConfig, ROOT_MOTION_PATH};
+use serde::{de::DeserializeOwned, Serialize};
+
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Serialize, Default)]
+struct AssetReport {
+    path: String,
+    // Where the game refers to it
+    referenced_by: Vec<&'static str>,
+    exists: bool,
+    errors: Vec<String>,
+    warnings: Vec<String>,
+    // glTF animation names, in file order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    animations: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+struct Report {
+    ok: bool,
+    checked: usize,
+    errors: usize,
+    warnings: usize,
+    assets: Vec<AssetReport>,
+}
+
+// What's expected to be in a file, beyond existing
+type Check = fn(&[u8], &mut AssetReport);
+
+fn main() {
+    let mut assets: BTreeMap<&'static str, (Vec<&'static str>, Check)> = BTreeMap::new();
+    let mut register = |path: &'static str, referenced_by: &'static str, check: Check| {
+        assets.entry(path).or_insert_with(|| (Vec::new(), check)).0.push(referenced_by);
+    };
+
+    // First so it gets the full animation check, `setup` loads it too
+    register(PROTAGONIST_GLB, "animation graph", check_protagonist);
+    for path in REGISTERED_ASSETS {
+        register(path, "setup", check_by_extension);
+    }
+    for cue in SoundCue::ALL {
+        register(cue.path(), "sound cues", check_by_extension);
+    }
+    for ambience in Ambience::ALL {
+        register(ambience.path(), "ambience", check_by_extension);
+    }
+
+    register(LEVEL_PATH, "level", |bytes, report| {
+        parse::<LevelData>(bytes, report);
+    });
+    register(OBJECTIVES_PATH, "objectives", |bytes, report| {
+        if let Err(err) = Objectives::from_ron(&String::from_utf8_lossy(bytes)) {
+            report.errors.push(err);
+        }
+    });
+    register(CONTROLLER_CONFIG_PATH, "controller", |bytes, report| {
+        parse::<ControllerConfig>(bytes, report);
+    });
+    register(ROCKS_CONFIG_PATH, "rocks", |bytes, report| {
+        if let Some(config) = parse::<RockConfig>(bytes, report) {
+            for (name, shape) in [("glacier", &config.glacier), ("monolith", &config.monolith)] {
+                if shape.facets < 3 || shape.rings < 1 {
+                    report.errors.push(format!("{} needs at least 3 facets and 1 ring", name));
+                }
+            }
+        }
+    });
+    register(ROOT_MOTION_PATH, "root motion", |bytes, report| {
+        if let Some(config) = parse::<RootMotionConfig>(bytes, report) {
+            check_clip_names(config.clips.keys(), report);
+        }
+    });
+    register(BLEND_SPACES_PATH, "blend spaces", |bytes, report| {
+        if let Some(config) = parse::<BlendSpacesConfig>(bytes, report) {
+            let clips = config
+                .speed
+                .iter()
+                .map(|sample| &sample.clip)
+                .chain(config.strafe.iter().map(|sample| &sample.clip))
+                .chain(config.replaces.iter());
+            check_clip_names(clips, report);
+        }
+    });
+    register(ANIMATION_EVENTS_PATH, "animation events", |bytes, report| {
+        if let Some(events) = parse::<HashMap<String, Vec<MarkerTime>>>(bytes, report) {
+            check_clip_names(events.keys(), report);
+            for (clip, markers) in &events {
+                if markers.iter().any(|marker| !(0.0..=1.0).contains(&marker.at)) {
+                    report.errors.push(format!("{} has a marker outside 0 to 1", clip));
+                }
+            }
+        }
+    });
+
+    let assets: Vec<AssetReport> = assets
+        .into_iter()
+        .map(|(path, (referenced_by, check))| {
+            let mut report = AssetReport {
+                path: path.to_string(),
+                referenced_by,
+                ..Default::default()
+            };
+            match std::fs::read(asset_path(path)) {
+                Ok(bytes) => {
+                    report.exists = true;
+                    check(&bytes, &mut report);
+                }
+                Err(err) => report.errors.push(format!("Can't read: {}", err)),
+            }
+            report
+        })
+        .collect();
+
+    let errors = assets.iter().map(|asset| asset.errors.len()).sum();
+    let report = Report {
+        ok: errors == 0,
+        checked: assets.len(),
+        errors,
+        warnings: assets.iter().map(|asset| asset.warnings.len()).sum(),
+        assets,
+    };
+
+    println!("{}", serde_json::to_string_pretty(&report).expect("report serializes"));
+    if !report.ok {
+        std::process::exit(1);
+    }
+}
+
+fn parse<T: DeserializeOwned>(bytes: &[u8], report: &mut AssetReport) -> Option<T> {
+    match ron::from_str(&String::from_utf8_lossy(bytes)) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            report.errors.push(format!("Doesn't parse: {}", err));
+            None
+        }
+    }
+}
+
+fn check_clip_names<'a>(names: impl Iterator<Item = &'a String>, report: &mut AssetReport) {
+    for name in names {
+        if !SCENES.contains_key(name.as_str()) {
+            report.errors.push(format!("Clip {} isn't in SCENES", name));
+        }
+    }
+}
+
+// Binary files get their header checked, which catches the usual empty files,
+// LFS pointers and HTML error pages saved under the wrong name
+fn check_by_extension(bytes: &[u8], report: &mut AssetReport) {
+    let extension = report.path.rsplit('.').next().unwrap_or_default().to_lowercase();
+    let magic: &[&[u8]] = match extension.as_str() {
+        "glb" | "gltf" => {
+            read_gltf(bytes, report);
+            return;
+        }
+        "png" => &[b"\x89PNG\r\n\x1a\n"],
+        "jpg" | "jpeg" => &[b"\xff\xd8\xff"],
+        "ktx2" => &[b"\xabKTX 20\xbb\r\n\x1a\n"],
+        "ogg" => &[b"OggS"],
+        _ => {
+            report.warnings.push(format!("Don't know how to check .{} files", extension));
+            return;
+        }
+    };
+    if !magic.iter().any(|magic| bytes.starts_with(magic)) {
+        report.errors.push(format!("Not a valid .{} file", extension));
+    }
+}
+
+fn read_gltf(bytes: &[u8], report: &mut AssetReport) -> Option<Vec<String>> {
+    match gltf::Gltf::from_slice(bytes) {
+        Ok(gltf) => {
+            let names: Vec<String> = gltf
+                .animations()
+                .map(|animation| animation.name().unwrap_or_default().to_string())
+                .collect();
+            report.animations = Some(names.clone());
+            Some(names)
+        }
+        Err(err) => {
+            report.errors.push(format!("Doesn't parse as glTF: {}", err));
+            None
+        }
+    }
+}
+
+// Lowercase letters and digits only, so `LEFT_SHOULDER_ADVANCE` matches "Left Shoulder Advance"
+fn normalized(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// The clip catalogue against what's actually in the file
+fn check_protagonist(bytes: &[u8], report: &mut AssetReport) {
+    let Some(names) = read_gltf(bytes, report) else {
+        return;
+    };
+
+    let expected = PROTAGONIST_ANIMATIONS + 1;
+    if names.len() < expected {
+        report.errors.push(format!("{} animations, the graph loads {}", names.len(), expected));
+    } else if names.len() > expected {
+        report.warnings.push(format!("{} animations, only the first {} are used", names.len(), expected));
+    }
+
+    let mut catalogue: Vec<(&str, usize)> = SCENES.iter().map(|(&name, &index)| (name, index)).collect();
+    catalogue.sort_by_key(|&(name, index)| (index, name));
+
+    let mut by_index: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+    for &(name, index) in &catalogue {
+        by_index.entry(index).or_default().push(name);
+
+        let Some(gltf_name) = names.get(index) else {
+            report.errors.push(format!("{} is animation {}, the file stops at {}", name, index, names.len()));
+            continue;
+        };
+        if !normalized(gltf_name).contains(&normalized(name)) {
+            report.warnings.push(format!("{} is animation {}, which the file calls {:?}", name, index, gltf_name));
+        }
+    }
+
+    for (index, clip_names) in by_index {
+        if clip_names.len() > 1 {
+            report.warnings.push(format!("Animation {} is in SCENES as {}", index, clip_names.join(" and ")));
+        }
+    }
+
+    // Optional, dying holds the last pose without it
+    if !SCENES.contains_key("DEATH") {
+        report.warnings.push("DEATH isn't in SCENES, dying won't animate".to_string());
+    }
+}
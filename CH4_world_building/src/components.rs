@@ -3,3 +3,7 @@ use bevy::prelude::*;
 // Marker component for the protagonist
 #[derive(Component)]
 pub struct Protagonist;
+
+// Marker component for the starship scene root
+#[derive(Component)]
+pub struct Starship;
@@ -0,0 +1,18 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum PortalKind {
+    // The sensor on the surface that drops you into the aquifer
+    Top,
+    // The sensor under the ice that throws you back up
+    Bottom,
+}
+
+// Things that happen in the world that story and objectives care about
+#[derive(Event, Debug, Clone)]
+pub enum GameEvent {
+    ReachedStarship,
+    BeaconPlaced { position: Vec3 },
+    PortalTraversed(PortalKind),
+}
@@ -0,0 +1,335 @@
+//! Drives the headless game tick by tick with scripted actions, for gameplay regression tests
+
+use bevy::prelude::*;
+use avian3d::prelude::*;
+use crate::components::Protagonist;
+use crate::headless_app;
+use crate::resources::WorldSeed;
+use crate::systems::actions::{Action, ScriptedActions};
+use crate::systems::audio::{Ambience, AudioEnvironment, CueSound, PlaySound, SoundCue, Surface};
+use crate::systems::climb::{ClimbState, Climbable};
+use crate::systems::stance::Stance;
+use crate::systems::loading::GameState;
+use crate::systems::markers::{AnimationMarker, AnimationMarkerReached};
+use crate::systems::portal::TopPortalSensor;
+use crate::systems::protagonist::Grounded;
+use crate::systems::replay::state_hash;
+
+use std::collections::HashSet;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 60 Hz, same as `HEADLESS_TICK`
+pub const TICKS_PER_SECOND: usize = 60;
+const HARNESS_SEED: u64 = 4;
+// Assets still come off disk on the IO threads, so loading is waited on in wall time
+const LOADING_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct Harness {
+    pub app: App,
+}
+
+impl Harness {
+    /// The CH4 world, set up and ready to take actions
+    pub fn new() -> Self {
+        let mut app = headless_app();
+        // Same world every time
+        app.insert_resource(WorldSeed(HARNESS_SEED));
+        app.finish();
+        app.cleanup();
+        // Startup systems
+        app.update();
+
+        let mut harness = Self { app };
+        harness.hold(&[]);
+        // The stand-in animation player gets attached and set up, and the rest of loading finishes
+        let started = Instant::now();
+        while *harness.app.world().resource::<State<GameState>>().get() == GameState::Loading {
+            assert!(started.elapsed() < LOADING_TIMEOUT, "still loading after {:?}", LOADING_TIMEOUT);
+            thread::sleep(Duration::from_millis(1));
+            harness.tick(1);
+        }
+        harness
+    }
+
+    /// Hold exactly these actions from the next tick on
+    pub fn hold(&mut self, actions: &[Action]) {
+        let held: HashSet<Action> = actions.iter().copied().collect();
+        self.app.world_mut().resource_mut::<ScriptedActions>().0 = Some(held);
+    }
+
+    pub fn tick(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            self.app.update();
+        }
+    }
+
+    /// Ticks until `condition` holds, giving up after `max_ticks`. Returns how many ticks it took.
+    pub fn tick_until(&mut self, max_ticks: usize, mut condition: impl FnMut(&mut Self) -> bool) -> Option<usize> {
+        for ticks in 0..=max_ticks {
+            if condition(self) {
+                return Some(ticks);
+            }
+            self.tick(1);
+        }
+        None
+    }
+
+    pub fn protagonist(&mut self) -> Entity {
+        self.app
+            .world_mut()
+            .query_filtered::<Entity, With<Protagonist>>()
+            .single(self.app.world())
+    }
+
+    pub fn get<C: Component>(&mut self) -> Option<&C> {
+        let protagonist = self.protagonist();
+        self.app.world().get::<C>(protagonist)
+    }
+
+    pub fn has<C: Component>(&mut self) -> bool {
+        self.get::<C>().is_some()
+    }
+
+    pub fn translation(&mut self) -> Vec3 {
+        self.get::<Transform>().unwrap().translation
+    }
+
+    pub fn set_translation(&mut self, translation: Vec3) {
+        let protagonist = self.protagonist();
+        self.app.world_mut().get_mut::<Transform>(protagonist).unwrap().translation = translation;
+    }
+
+    pub fn state_hash(&mut self) -> u64 {
+        let world = self.app.world_mut();
+        let mut bodies = world.query_filtered::<(&Transform, Option<&LinearVelocity>), With<RigidBody>>();
+        state_hash(bodies.iter(world))
+    }
+
+    /// Cues sent since this was last called
+    pub fn take_sounds(&mut self) -> Vec<SoundCue> {
+        let mut events = self.app.world_mut().resource_mut::<Events<PlaySound>>();
+        events.drain().map(|sound| sound.cue).collect()
+    }
+
+    pub fn find<C: Component>(&mut self) -> Entity {
+        self.app
+            .world_mut()
+            .query_filtered::<Entity, With<C>>()
+            .single(self.app.world())
+    }
+}
+
+#[test]
+fn top_portal_drops_into_the_aquifer() {
+    let mut harness = Harness::new();
+    let sensor = harness.find::<TopPortalSensor>();
+    let sensor_position = harness.app.world().get::<Transform>(sensor).unwrap().translation;
+
+    harness.set_translation(sensor_position);
+    harness.tick(TICKS_PER_SECOND / 2);
+
+    let y = harness.translation().y;
+    assert!(y < -15.0, "protagonist at y = {y} after touching the top portal");
+}
+
+#[test]
+fn jump_lands_within_two_seconds() {
+    let mut harness = Harness::new();
+    harness
+        .tick_until(2 * TICKS_PER_SECOND, |harness| harness.has::<Grounded>())
+        .expect("protagonist never settled on the ground");
+
+    // The jetpack fires when Jump is let go
+    harness.hold(&[Action::Jump]);
+    harness.tick(1);
+    harness.hold(&[]);
+
+    harness
+        .tick_until(TICKS_PER_SECOND / 2, |harness| !harness.has::<Grounded>())
+        .expect("protagonist never left the ground");
+    harness
+        .tick_until(2 * TICKS_PER_SECOND, |harness| harness.has::<Grounded>())
+        .expect("protagonist didn't land within 2 s");
+}
+
+#[test]
+fn walking_forward_moves_the_protagonist() {
+    let mut harness = Harness::new();
+    harness.tick_until(2 * TICKS_PER_SECOND, |harness| harness.has::<Grounded>());
+    let start = harness.translation();
+
+    harness.hold(&[Action::Forward]);
+    harness.tick(TICKS_PER_SECOND);
+
+    let moved = harness.translation().with_y(0.0).distance(start.with_y(0.0));
+    assert!(moved > 2.0, "only moved {moved} in a second of walking");
+}
+
+#[test]
+fn turning_right_on_a_ledge_shimmies_right() {
+    let mut harness = Harness::new();
+    harness.tick_until(2 * TICKS_PER_SECOND, |harness| harness.has::<Grounded>());
+    let start = harness.translation();
+
+    // A low wall just in front, its top within reach
+    harness.app.world_mut().spawn((
+        RigidBody::Static,
+        Climbable,
+        Collider::cuboid(10.0, 2.0, 1.0),
+        TransformBundle::from_transform(Transform::from_translation(start.with_y(1.1) - Vec3::Z * 2.0)),
+    ));
+    let protagonist = harness.protagonist();
+    harness.app.world_mut().get_mut::<Transform>(protagonist).unwrap().rotation = Quat::IDENTITY;
+
+    // Facing -Z, so right is +X
+    harness.hold(&[Action::Forward]);
+    harness
+        .tick_until(TICKS_PER_SECOND, |harness| harness.has::<ClimbState>())
+        .expect("never grabbed the ledge");
+    harness.hold(&[]);
+    harness.tick(1);
+    let hanging = harness.translation();
+
+    harness.hold(&[Action::TurnRight]);
+    harness.tick(TICKS_PER_SECOND / 2);
+    let right = harness.translation();
+    assert!(right.x - hanging.x > 0.3, "TurnRight shimmied from x = {} to {}", hanging.x, right.x);
+
+    harness.hold(&[Action::TurnLeft]);
+    harness.tick(TICKS_PER_SECOND);
+    let left = harness.translation();
+    assert!(left.x < hanging.x - 0.3, "TurnLeft shimmied from x = {} to {}", right.x, left.x);
+    assert!(harness.has::<ClimbState>(), "fell off while shimmying");
+}
+
+#[test]
+fn steps_climb_to_the_top_of_the_north_wall() {
+    let mut harness = Harness::new();
+    harness.tick_until(2 * TICKS_PER_SECOND, |harness| harness.has::<Grounded>());
+
+    // On the floor in front of the steps, facing the wall
+    harness.set_translation(Vec3::new(0.0, 1.0, -31.0));
+    let protagonist = harness.protagonist();
+    harness.app.world_mut().get_mut::<Transform>(protagonist).unwrap().rotation = Quat::IDENTITY;
+    harness.tick_until(TICKS_PER_SECOND, |harness| harness.has::<Grounded>());
+
+    // Four steps, then the wall itself. Each climb can end hanging from the next
+    // step, so go by the state rather than waiting for the climb to be over.
+    let is_hanging = |harness: &mut Harness| matches!(harness.get::<ClimbState>(), Some(ClimbState::Hanging(_)));
+    let is_mantling = |harness: &mut Harness| matches!(harness.get::<ClimbState>(), Some(ClimbState::Mantling { .. }));
+    for ledge in 1..=5 {
+        harness.hold(&[Action::Forward]);
+        harness
+            .tick_until(2 * TICKS_PER_SECOND, is_hanging)
+            .unwrap_or_else(|| panic!("never grabbed ledge {} from {}", ledge, harness.translation()));
+
+        // Pressing Forward again climbs up
+        harness.hold(&[]);
+        harness.tick(1);
+        harness.hold(&[Action::Forward]);
+        harness.tick(1);
+        assert!(is_mantling(&mut harness), "no room to climb onto ledge {}", ledge);
+        harness
+            .tick_until(2 * TICKS_PER_SECOND, |harness| !is_mantling(harness))
+            .unwrap_or_else(|| panic!("never climbed onto ledge {}", ledge));
+    }
+    harness.hold(&[]);
+    harness.tick(1);
+
+    // The north wall's top is at y = 10
+    let top = harness.translation();
+    assert!(top.y > 9.5 && top.z < -40.0, "ended up at {} rather than on the wall", top);
+}
+
+#[test]
+fn no_lying_down_with_a_wall_in_the_way() {
+    let mut harness = Harness::new();
+    harness.tick_until(2 * TICKS_PER_SECOND, |harness| harness.has::<Grounded>());
+    let start = harness.translation();
+
+    // Clear of the standing body, but not of the longer prone one
+    harness.app.world_mut().spawn((
+        RigidBody::Static,
+        Collider::cuboid(4.0, 2.0, 1.0),
+        TransformBundle::from_transform(Transform::from_translation(start.with_y(1.1) - Vec3::Z * 1.2)),
+    ));
+    let protagonist = harness.protagonist();
+    harness.app.world_mut().get_mut::<Transform>(protagonist).unwrap().rotation = Quat::IDENTITY;
+    harness.tick(1);
+
+    harness.hold(&[Action::Prone]);
+    harness.tick(1);
+    harness.hold(&[]);
+    harness.tick(1);
+
+    assert_eq!(harness.get::<Stance>(), Some(&Stance::Standing));
+}
+
+#[test]
+fn footsteps_sound_like_the_ground() {
+    let mut harness = Harness::new();
+    harness
+        .tick_until(2 * TICKS_PER_SECOND, |harness| harness.has::<Grounded>())
+        .expect("protagonist never settled on the ground");
+    harness.take_sounds();
+    let step = |harness: &mut Harness, marker: AnimationMarker| {
+        harness.app.world_mut().send_event(AnimationMarkerReached { player: Entity::PLACEHOLDER, marker });
+        harness.tick(1);
+        harness.take_sounds()
+    };
+
+    // The main floor has no surface of its own, so it's rock
+    assert_eq!(step(&mut harness, AnimationMarker::Footstep), [SoundCue::Footstep(Surface::Rock)]);
+
+    let ground = harness.get::<Grounded>().unwrap().entity;
+    harness.app.world_mut().entity_mut(ground).insert(Surface::Metal);
+    assert_eq!(step(&mut harness, AnimationMarker::Footstep), [SoundCue::Footstep(Surface::Metal)]);
+    assert_eq!(step(&mut harness, AnimationMarker::Landing), [SoundCue::Landing(Surface::Metal)]);
+    assert!(step(&mut harness, AnimationMarker::HandPlant).is_empty());
+}
+
+#[test]
+fn sounds_are_muffled_underwater() {
+    let mut harness = Harness::new();
+    let is_underwater = |harness: &mut Harness| {
+        harness.app.world().resource::<AudioEnvironment>().ambience == Ambience::Aquifer
+    };
+    assert!(!is_underwater(&mut harness), "muffled on the surface");
+
+    // The camera follows the protagonist down into the aquifer
+    harness.set_translation(Vec3::new(0.0, -40.0, 0.0));
+    harness
+        .tick_until(2 * TICKS_PER_SECOND, is_underwater)
+        .expect("listener never went underwater");
+
+    let mut cue_sounds = harness.app.world_mut().query_filtered::<(Entity, &PlaybackSettings), With<CueSound>>();
+    let before: HashSet<Entity> = cue_sounds.iter(harness.app.world()).map(|(entity, _)| entity).collect();
+    let cue = SoundCue::ChargeBeep;
+    harness.app.world_mut().send_event(PlaySound { cue, position: Vec3::new(0.0, -40.0, 0.0) });
+    harness.tick(1);
+
+    let (_, settings) = cue_sounds
+        .iter(harness.app.world())
+        .find(|(entity, _)| !before.contains(entity))
+        .expect("the cue never played");
+    assert!(settings.volume.get() < cue.volume(), "volume {} underwater", settings.volume.get());
+    assert!(settings.speed < 1.0, "playback speed {} underwater", settings.speed);
+}
+
+#[test]
+fn same_actions_give_the_same_state() {
+    let run = || {
+        let mut harness = Harness::new();
+        harness.tick(TICKS_PER_SECOND);
+        harness.hold(&[Action::Forward, Action::Run]);
+        harness.tick(TICKS_PER_SECOND);
+        harness.hold(&[Action::TurnLeft, Action::Jump]);
+        harness.tick(TICKS_PER_SECOND / 4);
+        harness.hold(&[]);
+        harness.tick(TICKS_PER_SECOND);
+        harness.state_hash()
+    };
+
+    assert_eq!(run(), run(), "the simulation isn't deterministic");
+}
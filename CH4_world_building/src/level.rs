@@ -0,0 +1,104 @@
+use bevy::{
+    asset::io::file::FileAssetReader,
+    prelude::*,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+
+use std::fs;
+use std::path::PathBuf;
+
+// Level file loaded at startup, relative to the assets folder
+pub const LEVEL_PATH: &str = "levels/world.ron";
+
+/// Everything a level file can declare. Positions are plain arrays so the
+/// files stay readable without bevy's serialize feature.
+#[derive(Resource, Deserialize, Debug, Clone)]
+pub struct LevelData {
+    pub player_start: SpawnPoint,
+    #[serde(default)]
+    pub checkpoints: Vec<CheckpointData>,
+    // The aquifer, the built in bounds are used if it's left out
+    #[serde(default)]
+    pub water: Option<BoxData>,
+}
+
+// Axis aligned box given by its corners
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct BoxData {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SpawnPoint {
+    pub position: [f32; 3],
+    // Facing around the Y axis, in radians
+    #[serde(default)]
+    pub yaw: f32,
+}
+
+impl SpawnPoint {
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(Vec3::from_array(self.position))
+            .with_rotation(Quat::from_rotation_y(self.yaw))
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CheckpointData {
+    pub name: String,
+    // Where the protagonist respawns after touching this checkpoint
+    pub spawn: SpawnPoint,
+    // Full size of the trigger volume, centered on the spawn position
+    pub size: [f32; 3],
+}
+
+impl Default for LevelData {
+    fn default() -> Self {
+        Self {
+            player_start: SpawnPoint {
+                position: [0.0, 1.0, 0.0],
+                yaw: 0.0,
+            },
+            checkpoints: Vec::new(),
+            water: None,
+        }
+    }
+}
+
+impl LevelData {
+    /// Reads a level file from the assets folder, falling back to the default
+    /// level (start at the origin, no checkpoints) if it's missing or broken.
+    pub fn load(path: &str) -> Self {
+        read_ron_asset(path).unwrap_or_else(|| {
+            warn!("Using the default level");
+            Self::default()
+        })
+    }
+}
+
+// Where a file in the assets folder is on disk
+pub fn asset_path(path: &str) -> PathBuf {
+    FileAssetReader::new("assets").root_path().join(path)
+}
+
+/// Reads and parses a RON file from the assets folder, logging what went wrong if it can't
+pub fn read_ron_asset<T: DeserializeOwned>(path: &str) -> Option<T> {
+    let full_path = asset_path(path);
+
+    let contents = match fs::read_to_string(&full_path) {
+        Ok(contents) => contents,
+        Err(err) => {
+            warn!("Couldn't read {:?}: {}", full_path, err);
+            return None;
+        }
+    };
+
+    match ron::from_str(&contents) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            warn!("Couldn't parse {:?}: {}", full_path, err);
+            None
+        }
+    }
+}
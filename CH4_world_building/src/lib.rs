@@ -0,0 +1,461 @@
+// Chatty logging that only dev builds print. The arguments still count as used in
+// other builds, so nothing needs its own cfg.
+macro_rules! dev_log {
+    ($($arg:tt)*) => {
+        if cfg!(feature = "dev") {
+            bevy::log::info!($($arg)*);
+        }
+    };
+}
+
+pub mod systems;
+pub mod components;
+pub mod resources;
+pub mod level;
+pub mod events;
+#[cfg(test)]
+mod harness;
+
+use crate::components::Protagonist;
+use crate::resources::{Animations, TimeOfDay, WorldSeed, SCENES};
+
+use systems::placeholders::register_placeholder_source;
+use systems::portal::portal_system;
+use systems::setup::{setup, reset_protagonist};
+use systems::camera::rotate_camera;
+use systems::input::{
+    keyboard_animation_control, 
+    blink_lights, 
+    handle_temporary_lights,
+    detonate_charges,
+    pick_up_charges,
+    place_charges,
+};
+use systems::actions::{ActionState, InputMap, ScriptedActions, update_action_state};
+use systems::interaction::{
+    InteractionTarget,
+    Interacted,
+    select_interaction_target,
+    interact_system,
+};
+use systems::protagonist::{update_grounded, follow_ground};
+use systems::health::{
+    DamageEvent,
+    Explosion,
+    RespawnPoint,
+    apply_fall_damage,
+    apply_explosion_damage,
+    apply_hazard_damage,
+    handle_damage,
+    respawn_dead,
+};
+use systems::checkpoint::{
+    CheckpointProgress,
+    CheckpointReached,
+    checkpoint_system,
+};
+use systems::objectives::{
+    ObjectiveCompleted,
+    Objectives,
+    update_objectives,
+    starship_proximity_system,
+};
+use systems::climb::{grab_ledges, climb_system};
+use systems::stance::{change_stance, update_noise};
+use systems::swim::{update_swimming, swim_system};
+use systems::environment::{
+    WaterVolume,
+    WorldGravity,
+    update_gravity,
+    update_underwater_lighting,
+};
+use systems::controller::{
+    CONTROLLER_CONFIG_PATH,
+    ControllerConfig,
+    kinematic_controller,
+};
+use systems::root_motion::{
+    ROOT_MOTION_PATH,
+    RootMotionConfig,
+    RootMotionClips,
+    tag_root_bone,
+    apply_root_motion,
+    strip_root_motion,
+};
+use systems::blend::{BlendSpaces, update_blend_spaces};
+use systems::markers::{
+    AnimationMarkerReached,
+    fire_animation_markers,
+    spawn_dust_puffs,
+    update_dust_puffs,
+};
+use systems::audio::{
+    AudioEnvironment,
+    PlaySound,
+    setup_audio,
+    update_audio_environment,
+    footstep_sounds,
+    game_event_sounds,
+    play_sounds,
+    update_ambience,
+};
+use systems::replay::{
+    replay_actions,
+    record_actions,
+    finish_recording,
+};
+use systems::rocks::{RockConfig, ROCKS_CONFIG_PATH};
+use systems::baked_colliders::bake_scene_colliders;
+use systems::props::{PropRegistry, register_asset_diagnostics, measure_asset_counts};
+use systems::hud::{setup_hud, update_objective_hud, update_interaction_prompt};
+use systems::loading::{
+    GameState,
+    Gameplay,
+    LoadingProgress,
+    setup_loading_screen,
+    track_loading,
+    update_loading_screen,
+    finish_loading,
+};
+use events::GameEvent;
+
+use avian3d::prelude::*;
+use bevy::{
+    animation::animate_targets,
+    app::ScheduleRunnerPlugin,
+    diagnostic::DiagnosticsPlugin,
+    input::{InputPlugin, InputSystem},
+    pbr::DirectionalLightShadowMap,
+    prelude::*,
+    scene::ScenePlugin,
+    state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+    transform::TransformSystem,
+};
+
+use std::f32::consts::*;
+use std::time::Duration;
+
+// Fixed step for headless runs and recordings
+pub const HEADLESS_TICK: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// The game in a window, as players get it
+pub fn windowed_app() -> App {
+    let mut app = App::new();
+    register_placeholder_source(&mut app);
+    app.insert_resource(DirectionalLightShadowMap { size: 4096 })
+        // Enable physics
+        .add_plugins((DefaultPlugins, PhysicsPlugins::default()));
+    add_game(&mut app);
+
+    // Gizmo overlay, needs the renderer so it's not in `add_game`
+    #[cfg(feature = "dev")]
+    add_debug_overlay(&mut app);
+    app
+}
+
+/// Headless version of the game for automated runs and tests: the engine plugins
+/// the simulation needs, without a window, renderer or audio device. Time advances
+/// by exactly one 60 Hz tick per update.
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    register_placeholder_source(&mut app);
+    app.add_plugins((
+        MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(HEADLESS_TICK)),
+        AssetPlugin::default(),
+        TransformPlugin,
+        HierarchyPlugin,
+        InputPlugin,
+        StatesPlugin,
+        DiagnosticsPlugin,
+        ScenePlugin,
+        AnimationPlugin,
+        PhysicsPlugins::default(),
+    ))
+    // Nothing draws or plays these, but setup still creates and loads them
+    .init_asset::<Mesh>()
+    .init_asset::<StandardMaterial>()
+    .init_asset::<Image>()
+    .init_asset::<AudioSource>()
+    .insert_resource(TimeUpdateStrategy::ManualDuration(HEADLESS_TICK))
+    .insert_resource(Time::<Fixed>::from_duration(HEADLESS_TICK))
+    .add_systems(Update, attach_stand_in_player);
+    add_game(&mut app);
+    app
+}
+
+// Without glTF scenes the protagonist never gets its rig, but the movement code runs
+// through its `AnimationPlayer`, so give it a bare one
+fn attach_stand_in_player(mut commands: Commands, protagonist_query: Query<Entity, Added<Protagonist>>) {
+    for protagonist in &protagonist_query {
+        commands.spawn(AnimationPlayer::default()).set_parent(protagonist);
+    }
+}
+
+/// Everything the game adds on top of the engine plugins
+fn add_game(app: &mut App) {
+    app
+        .add_event::<DamageEvent>()
+        .add_event::<Explosion>()
+        .init_resource::<RespawnPoint>()
+        .add_event::<CheckpointReached>()
+        .init_resource::<CheckpointProgress>()
+        .add_event::<GameEvent>()
+        .add_event::<ObjectiveCompleted>()
+        .init_resource::<Objectives>()
+        .init_resource::<InputMap>()
+        .init_resource::<ActionState>()
+        .init_resource::<ScriptedActions>()
+        .init_resource::<WorldSeed>()
+        .init_resource::<TimeOfDay>()
+        .init_resource::<WorldGravity>()
+        .init_resource::<InteractionTarget>()
+        .init_resource::<WaterVolume>()
+        .init_resource::<PropRegistry>()
+        .insert_resource(ControllerConfig::load(CONTROLLER_CONFIG_PATH))
+        .insert_resource(RootMotionConfig::load(ROOT_MOTION_PATH))
+        .insert_resource(RockConfig::load(ROCKS_CONFIG_PATH))
+        .init_resource::<RootMotionClips>()
+        .add_event::<Interacted>()
+        .add_event::<AnimationMarkerReached>()
+        .add_event::<PlaySound>()
+        .init_resource::<AudioEnvironment>()
+        .init_state::<GameState>()
+        .init_resource::<LoadingProgress>()
+        // Nothing moves until the level is all there
+        .configure_sets(Update, Gameplay.run_if(in_state(GameState::Playing)))
+        .add_systems(Startup, (setup, setup_hud, setup_audio, setup_loading_screen))
+        .add_systems(Update, (track_loading, update_loading_screen)
+            .chain()
+            .run_if(in_state(GameState::Loading)))
+        .add_systems(OnEnter(GameState::Playing), finish_loading)
+        .add_systems(PreUpdate, update_action_state.after(InputSystem))
+        .add_systems(PreUpdate, (
+            replay_actions.before(update_action_state),
+            record_actions.after(update_action_state),
+        ))
+        .add_systems(Last, finish_recording)
+        .add_systems(Update, animate_light_direction)
+        .add_systems(Update, rotate_camera)
+        .add_systems(Update, setup_scene_once_loaded.before(animate_targets))
+        .add_systems(Update, bake_scene_colliders)
+        .add_systems(Update, update_grounded.before(keyboard_animation_control).in_set(Gameplay))
+        .add_systems(Update, keyboard_animation_control.in_set(Gameplay))
+        .add_systems(Update, (climb_system, grab_ledges).chain().before(keyboard_animation_control).in_set(Gameplay))
+        .add_systems(Update, change_stance.before(keyboard_animation_control).in_set(Gameplay))
+        .add_systems(Update, follow_ground.after(keyboard_animation_control).in_set(Gameplay))
+        .add_systems(Update, tag_root_bone)
+        .add_systems(Update, update_blend_spaces
+            .after(keyboard_animation_control)
+            .after(apply_root_motion))
+        .add_systems(Update, apply_root_motion
+            .after(keyboard_animation_control)
+            .before(follow_ground)
+            .in_set(Gameplay))
+        // Between the animation being applied and the transforms being propagated
+        .add_systems(PostUpdate, strip_root_motion
+            .after(animate_targets)
+            .before(TransformSystem::TransformPropagate))
+        .add_systems(Update, (update_swimming, swim_system)
+            .chain()
+            .after(update_grounded)
+            .before(keyboard_animation_control)
+            .in_set(Gameplay))
+        .add_systems(Update, fire_animation_markers)
+        .add_systems(Update, update_noise
+            .after(keyboard_animation_control)
+            .after(fire_animation_markers)
+            .in_set(Gameplay))
+        .add_systems(Update, (place_charges, spawn_dust_puffs).after(fire_animation_markers).in_set(Gameplay))
+        .add_systems(Update, update_dust_puffs)
+        .add_systems(Update, detonate_charges.in_set(Gameplay))
+        .add_systems(Update, (
+            select_interaction_target,
+            interact_system,
+            pick_up_charges,
+            update_interaction_prompt,
+        ).chain().in_set(Gameplay))
+        .add_systems(Update, (
+            apply_fall_damage.after(update_grounded),
+            apply_explosion_damage,
+            apply_hazard_damage,
+            handle_damage,
+            respawn_dead,
+        ).chain().in_set(Gameplay))
+        // Runs last so it sees every other system's velocity for the frame
+        .add_systems(Update, kinematic_controller
+            .after(follow_ground)
+            .after(swim_system)
+            .after(climb_system)
+            .after(respawn_dead)
+            .in_set(Gameplay))
+        .add_systems(Update, reset_game_on_command_r.in_set(Gameplay)) // Add reset system
+        .add_systems(Update, (update_gravity, update_underwater_lighting))
+        .add_systems(Update, (portal_system, checkpoint_system, starship_proximity_system).in_set(Gameplay))
+        .add_systems(Update, (update_objectives, update_objective_hud).chain().in_set(Gameplay))
+        .add_systems(Update, blink_lights)
+        .add_systems(Update, handle_temporary_lights)
+        // Audio: gameplay sends `PlaySound`, these turn it into sound
+        .add_systems(Update, (
+            footstep_sounds.after(fire_animation_markers),
+            game_event_sounds,
+        ).before(play_sounds))
+        .add_systems(Update, (update_audio_environment, play_sounds, update_ambience).chain())
+        .add_systems(Update, measure_asset_counts);
+    register_asset_diagnostics(app);
+
+    #[cfg(feature = "dev")]
+    add_dev_tools(app);
+}
+
+/// Cheat keys and the developer console, only in builds with the `dev` feature
+#[cfg(feature = "dev")]
+fn add_dev_tools(app: &mut App) {
+    use systems::cheats::cheat_keys;
+    use systems::console::{
+        Console,
+        ConsoleAppExt,
+        ConsoleCommands,
+        setup_console,
+        console_input,
+        block_game_input,
+        run_console_commands,
+        update_console_text,
+        help_command,
+        clear_command,
+    };
+    use systems::dev_commands::{
+        SPAWNABLE,
+        tp_command,
+        spawn_command,
+        gravity_command,
+        anim_command,
+        time_command,
+        seed_command,
+        reset_command,
+    };
+
+    use bevy::diagnostic::LogDiagnosticsPlugin;
+    use systems::props::{MESH_COUNT, MATERIAL_COUNT, SHARED_PROP_COUNT};
+
+    app
+        // Asset counts in the log every so often, to catch props making their own copies
+        .add_plugins(LogDiagnosticsPlugin {
+            wait_duration: Duration::from_secs(10),
+            filter: Some(vec![MESH_COUNT, MATERIAL_COUNT, SHARED_PROP_COUNT]),
+            ..default()
+        })
+        .init_resource::<Console>()
+        .init_resource::<ConsoleCommands>()
+        .add_systems(Startup, setup_console)
+        .add_systems(PreUpdate, block_game_input.after(InputSystem).before(update_action_state))
+        .add_systems(Update, cheat_keys.after(keyboard_animation_control).in_set(Gameplay))
+        .add_systems(Update, (console_input, run_console_commands, update_console_text).chain());
+
+    let mut clips: Vec<&str> = SCENES.keys().copied().collect();
+    clips.sort();
+    app
+        .add_console_command("help", "- list the commands", &[], help_command)
+        .add_console_command("clear", "- clear the console", &[], clear_command)
+        .add_console_command("tp", "x y z - move the protagonist", &[], tp_command)
+        .add_console_command("spawn", "container - drop one in front of the protagonist", &SPAWNABLE, spawn_command)
+        .add_console_command("gravity", "x y z - gravity outside the water", &[], gravity_command)
+        .add_console_command("anim", "CLIP - play an animation clip", &clips, anim_command)
+        .add_console_command("time", "HH:MM [hours per second] - move the sun", &[], time_command)
+        .add_console_command("seed", "N - place the random props from a new seed", &[], seed_command)
+        .add_console_command("reset", "- respawn at the last checkpoint", &[], reset_command);
+}
+
+/// Collider, zone and cast gizmos, only in builds with the `dev` feature
+#[cfg(feature = "dev")]
+fn add_debug_overlay(app: &mut App) {
+    use systems::debug::{
+        DebugCategory,
+        DebugOverlay,
+        overlay_shows,
+        toggle_debug_overlay,
+        sync_collider_debug,
+        draw_zones,
+        draw_portal_links,
+        draw_camera_arm,
+        draw_ground_casts,
+    };
+
+    app.add_plugins(PhysicsDebugPlugin::default())
+        .init_resource::<DebugOverlay>()
+        .add_systems(Update, (toggle_debug_overlay, sync_collider_debug).chain())
+        .add_systems(Update, (
+            draw_zones.run_if(overlay_shows(DebugCategory::Zones)),
+            draw_portal_links.run_if(overlay_shows(DebugCategory::PortalLinks)),
+            draw_camera_arm.run_if(overlay_shows(DebugCategory::CameraArm)),
+            draw_ground_casts.run_if(overlay_shows(DebugCategory::GroundCasts)),
+        ).after(toggle_debug_overlay));
+}
+
+fn animate_light_direction(
+    time: Res<Time>,
+    mut time_of_day: ResMut<TimeOfDay>,
+    mut query: Query<&mut Transform, With<DirectionalLight>>,
+) {
+    time_of_day.hour = (time_of_day.hour + time.delta_seconds() * time_of_day.speed).rem_euclid(24.0);
+
+    for mut transform in &mut query {
+        transform.rotation = Quat::from_euler(
+            EulerRot::ZYX,
+            0.0,
+            time_of_day.hour / 24.0 * TAU,
+            -FRAC_PI_4,
+        );
+    }
+}
+
+// Once the scene is loaded, start the animation
+fn setup_scene_once_loaded(
+    mut commands: Commands,
+    animations: Res<Animations>,
+    blend_spaces: Res<BlendSpaces>,
+    mut players: Query<(Entity, &mut AnimationPlayer), Added<AnimationPlayer>>,
+) {
+    for (entity, mut player) in &mut players {
+        let mut transitions = AnimationTransitions::new();
+        let stretch = *SCENES.get("IDLE_STRETCH").unwrap();
+        dev_log!("starting pose: {}", stretch);
+        // Make sure to start the animation via the `AnimationTransitions`
+        // component. The `AnimationTransitions` component wants to manage all
+        // the animations and will get confused if the animations are started
+        // directly via the `AnimationPlayer`.
+        transitions
+            .play(&mut player, 
+                animations.animations[stretch], 
+                Duration::from_millis(1000),
+            ).repeat();
+
+        // The blend space clips run all the time, weighted in and out by the graph
+        blend_spaces.start(&mut player);
+
+        commands
+            .entity(entity)
+            .insert(animations.graph.clone())
+            .insert(transitions);
+    }
+}
+
+/// System to reset the game when Command-R or Ctrl-R is pressed.
+/// The protagonist comes back fresh at the latest checkpoint (or the player start).
+fn reset_game_on_command_r(
+    mut commands: Commands,
+    protagonist_query: Query<Entity, With<Protagonist>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    respawn_point: Res<RespawnPoint>,
+    controller_config: Res<ControllerConfig>,
+) {
+    // Check for Command-R (Mac) or Ctrl-R (Other systems)
+    let is_command_r = keyboard_input.pressed(KeyCode::SuperLeft) && keyboard_input.just_pressed(KeyCode::KeyR);
+    let is_ctrl_r = keyboard_input.pressed(KeyCode::ControlLeft) && keyboard_input.just_pressed(KeyCode::KeyR);
+
+    if is_command_r || is_ctrl_r {
+        dev_log!("Resetting the game...");
+        reset_protagonist(&mut commands, &protagonist_query, &asset_server, &controller_config, respawn_point.0);
+    }
+}
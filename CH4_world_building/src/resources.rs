@@ -2,14 +2,69 @@ use bevy::prelude::*;
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 
+pub const PROTAGONIST_GLB: &str = "models/ProtagonistLowPoly/Protagonist.glb";
+// Index of the last animation in the protagonist's glTF
+pub const PROTAGONIST_ANIMATIONS: usize = 44;
+pub const STARSHIP_GLB: &str = "models/starhopper.glb";
+
+pub const MARS_TEXTURE: &str = "textures/8k_mars.png";
+pub const STARS_TEXTURE: &str = "textures/8k_stars.png";
+pub const ICE_TEXTURE: &str = "textures/ice_texture2.png";
+pub const ICE_TEXTURE_DARK: &str = "textures/ice_texture3.png";
+pub const STAR_WELL_TEXTURE: &str = "textures/star_well.png";
+pub const CONCRETE_TEXTURE: &str = "textures/concrete.png";
+pub const CONTAINER_TEXTURE: &str = "textures/container_metal.png";
+
+pub const DIFFUSE_MAP: &str = "environment_maps/pisa_diffuse_rgb9e5_zstd.ktx2";
+pub const SPECULAR_MAP: &str = "environment_maps/pisa_specular_rgb9e5_zstd.ktx2";
+
+// Every model, texture and map `setup` loads, for `check-assets`
+pub const REGISTERED_ASSETS: [&str; 11] = [
+    PROTAGONIST_GLB,
+    STARSHIP_GLB,
+    MARS_TEXTURE,
+    STARS_TEXTURE,
+    ICE_TEXTURE,
+    ICE_TEXTURE_DARK,
+    STAR_WELL_TEXTURE,
+    CONCRETE_TEXTURE,
+    CONTAINER_TEXTURE,
+    DIFFUSE_MAP,
+    SPECULAR_MAP,
+];
 
 #[derive(Resource)]
 pub struct Animations {
     pub animations: Vec<AnimationNodeIndex>,
-    #[allow(dead_code)]
+    // Blend node every clip in `animations` hangs off, faded out while the blend spaces play
+    pub flat_clips: AnimationNodeIndex,
     pub graph: Handle<AnimationGraph>,
 }
 
+// Seeds everything random in the level. Random per run unless a replay sets it.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct WorldSeed(pub u64);
+
+impl Default for WorldSeed {
+    fn default() -> Self {
+        Self(rand::random())
+    }
+}
+
+// Where the sun is, in hours from midnight. The day goes round every 10 seconds unless slowed down.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct TimeOfDay {
+    pub hour: f32,
+    // Game hours per real second
+    pub speed: f32,
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        Self { hour: 0.0, speed: 2.4 }
+    }
+}
+
 // Define the global `scenes` variable
 pub static SCENES: Lazy<HashMap<&'static str, usize>> = Lazy::new(|| {
     [
@@ -21,7 +76,6 @@ pub static SCENES: Lazy<HashMap<&'static str, usize>> = Lazy::new(|| {
         ("PIVOT_RIGHT", 13),
         ("HEARD_SOUND", 14),
         ("WALK_BACK", 16),
-        ("DEATH", 16),
         ("LEGS_UP", 18),
         ("LOOK_AROUND", 19),
         ("TREAD", 20),
@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use std::collections::{HashMap, HashSet};
+
+// Everything the player can do, gameplay systems read these instead of raw keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    Forward,
+    Backward,
+    TurnLeft,
+    TurnRight,
+    StrafeLeft,
+    StrafeRight,
+    Run,
+    Jump,
+    PlaceCharge,
+    Detonate,
+    Interact,
+    Crouch,
+    Prone,
+    // Swimming up and down
+    Ascend,
+    Descend,
+}
+
+impl Action {
+    pub const ALL: [Action; 15] = [
+        Action::Forward,
+        Action::Backward,
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::StrafeLeft,
+        Action::StrafeRight,
+        Action::Run,
+        Action::Jump,
+        Action::PlaceCharge,
+        Action::Detonate,
+        Action::Interact,
+        Action::Crouch,
+        Action::Prone,
+        Action::Ascend,
+        Action::Descend,
+    ];
+}
+
+// Which keys trigger which action, several keys can share an action and vice versa
+#[derive(Resource)]
+pub struct InputMap {
+    pub bindings: HashMap<Action, Vec<KeyCode>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self {
+            bindings: HashMap::from([
+                (Action::Forward, vec![KeyCode::KeyW]),
+                (Action::Backward, vec![KeyCode::KeyS]),
+                (Action::TurnLeft, vec![KeyCode::KeyA]),
+                (Action::TurnRight, vec![KeyCode::KeyD]),
+                (Action::StrafeLeft, vec![KeyCode::KeyQ]),
+                (Action::StrafeRight, vec![KeyCode::KeyE]),
+                (Action::Run, vec![KeyCode::ShiftLeft]),
+                (Action::Jump, vec![KeyCode::Space]),
+                (Action::PlaceCharge, vec![KeyCode::KeyC]),
+                (Action::Detonate, vec![KeyCode::KeyG]),
+                (Action::Interact, vec![KeyCode::KeyF]),
+                (Action::Crouch, vec![KeyCode::KeyZ]),
+                (Action::Prone, vec![KeyCode::KeyX]),
+                (Action::Ascend, vec![KeyCode::Space]),
+                (Action::Descend, vec![KeyCode::KeyZ]),
+            ]),
+        }
+    }
+}
+
+/// This frame's actions, same semantics as `ButtonInput`
+#[derive(Resource, Default)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    just_released: HashSet<Action>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        self.just_released.contains(&action)
+    }
+
+    /// Replaces the held actions and works out what was pressed or released since last frame
+    pub fn set_pressed(&mut self, pressed: HashSet<Action>) {
+        self.just_pressed = pressed.difference(&self.pressed).copied().collect();
+        self.just_released = self.pressed.difference(&pressed).copied().collect();
+        self.pressed = pressed;
+    }
+}
+
+// When set, the held actions come from here instead of the keyboard (tests, replays)
+#[derive(Resource, Default)]
+pub struct ScriptedActions(pub Option<HashSet<Action>>);
+
+pub fn update_action_state(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    scripted: Res<ScriptedActions>,
+    mut action_state: ResMut<ActionState>,
+) {
+    if let Some(pressed) = &scripted.0 {
+        action_state.set_pressed(pressed.clone());
+        return;
+    }
+
+    let pressed = input_map
+        .bindings
+        .iter()
+        .filter(|(_, keys)| keyboard_input.any_pressed(keys.iter().copied()))
+        .map(|(action, _)| *action)
+        .collect();
+
+    action_state.set_pressed(pressed);
+}
@@ -0,0 +1,308 @@
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
+use avian3d::prelude::*;
+use crate::components::Protagonist;
+use crate::events::GameEvent;
+use crate::systems::environment::WaterVolume;
+use crate::systems::health::Explosion;
+use crate::systems::markers::{AnimationMarker, AnimationMarkerReached};
+use crate::systems::protagonist::Grounded;
+
+use std::collections::HashMap;
+
+// How long the ambient loops take to cross over
+const AMBIENCE_FADE_SECS: f32 = 2.0;
+const AMBIENCE_VOLUME: f32 = 0.4;
+
+// Bevy's audio has no filters, so underwater everything gets quieter and a bit
+// slower, which pulls the pitch down and reads as muffled
+const UNDERWATER_MUFFLE: Muffle = Muffle { volume: 0.35, speed: 0.85 };
+const NO_MUFFLE: Muffle = Muffle { volume: 1.0, speed: 1.0 };
+
+// What a collider sounds like underfoot. On hierarchies it goes on the rigid body.
+#[derive(Component, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Surface {
+    #[default]
+    Rock,
+    Ice,
+    Metal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundCue {
+    Footstep(Surface),
+    Landing(Surface),
+    JetpackBurst,
+    PortalWhoosh,
+    ChargeBeep,
+    Explosion,
+}
+
+impl SoundCue {
+    pub fn path(self) -> &'static str {
+        match self {
+            SoundCue::Footstep(Surface::Rock) => "audio/footstep_rock.ogg",
+            SoundCue::Footstep(Surface::Ice) => "audio/footstep_ice.ogg",
+            SoundCue::Footstep(Surface::Metal) => "audio/footstep_metal.ogg",
+            SoundCue::Landing(Surface::Rock) => "audio/land_rock.ogg",
+            SoundCue::Landing(Surface::Ice) => "audio/land_ice.ogg",
+            SoundCue::Landing(Surface::Metal) => "audio/land_metal.ogg",
+            SoundCue::JetpackBurst => "audio/jetpack_burst.ogg",
+            SoundCue::PortalWhoosh => "audio/portal_whoosh.ogg",
+            SoundCue::ChargeBeep => "audio/charge_beep.ogg",
+            SoundCue::Explosion => "audio/explosion.ogg",
+        }
+    }
+
+    pub fn volume(self) -> f32 {
+        match self {
+            SoundCue::Footstep(_) => 0.5,
+            SoundCue::ChargeBeep => 0.6,
+            SoundCue::Explosion => 1.5,
+            _ => 1.0,
+        }
+    }
+
+    pub const ALL: [SoundCue; 10] = [
+        SoundCue::Footstep(Surface::Rock),
+        SoundCue::Footstep(Surface::Ice),
+        SoundCue::Footstep(Surface::Metal),
+        SoundCue::Landing(Surface::Rock),
+        SoundCue::Landing(Surface::Ice),
+        SoundCue::Landing(Surface::Metal),
+        SoundCue::JetpackBurst,
+        SoundCue::PortalWhoosh,
+        SoundCue::ChargeBeep,
+        SoundCue::Explosion,
+    ];
+}
+
+// A sound to play somewhere in the world. Gameplay sends these, only
+// `play_sounds` talks to the audio engine.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlaySound {
+    pub cue: SoundCue,
+    pub position: Vec3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Ambience {
+    Surface,
+    Aquifer,
+}
+
+impl Ambience {
+    pub const ALL: [Ambience; 2] = [Ambience::Surface, Ambience::Aquifer];
+
+    pub fn path(self) -> &'static str {
+        match self {
+            Ambience::Surface => "audio/ambience_surface.ogg",
+            Ambience::Aquifer => "audio/ambience_aquifer.ogg",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Muffle {
+    pub volume: f32,
+    pub speed: f32,
+}
+
+/// What the listener should be hearing, worked out from where it is
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct AudioEnvironment {
+    pub ambience: Ambience,
+    pub muffle: Muffle,
+}
+
+impl Default for AudioEnvironment {
+    fn default() -> Self {
+        Self {
+            ambience: Ambience::Surface,
+            muffle: NO_MUFFLE,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct SoundBank {
+    cues: HashMap<SoundCue, Handle<AudioSource>>,
+}
+
+// One of the ambient loops, faded in and out by `update_ambience`
+#[derive(Component)]
+pub struct AmbientLoop(pub Ambience);
+
+// Short one-off sounds, so the muffle can be applied to them too
+#[derive(Component)]
+pub struct CueSound {
+    base_volume: f32,
+}
+
+pub fn setup_audio(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let cues = SoundCue::ALL
+        .iter()
+        .map(|&cue| (cue, asset_server.load(cue.path())))
+        .collect();
+    commands.insert_resource(SoundBank { cues });
+
+    for ambience in Ambience::ALL {
+        commands.spawn((
+            AudioBundle {
+                source: asset_server.load(ambience.path()),
+                settings: PlaybackSettings {
+                    mode: PlaybackMode::Loop,
+                    volume: Volume::new(0.0),
+                    ..default()
+                },
+            },
+            AmbientLoop(ambience),
+        ));
+    }
+}
+
+/// Underwater means the aquifer loop and the muffle, anywhere else is the surface
+pub fn update_audio_environment(
+    water: Res<WaterVolume>,
+    listener_query: Query<&GlobalTransform, With<SpatialListener>>,
+    mut environment: ResMut<AudioEnvironment>,
+) {
+    let Ok(listener) = listener_query.get_single() else {
+        return;
+    };
+
+    let wanted = if water.contains(listener.translation()) {
+        AudioEnvironment { ambience: Ambience::Aquifer, muffle: UNDERWATER_MUFFLE }
+    } else {
+        AudioEnvironment { ambience: Ambience::Surface, muffle: NO_MUFFLE }
+    };
+
+    if *environment != wanted {
+        *environment = wanted;
+    }
+}
+
+// What's underfoot, through the collider's rigid body if it's part of a hierarchy
+fn surface_under(
+    grounded: Option<&Grounded>,
+    surfaces: &Query<&Surface>,
+    collider_parents: &Query<&ColliderParent>,
+) -> Surface {
+    let Some(grounded) = grounded else {
+        return Surface::default();
+    };
+    surfaces
+        .get(grounded.entity)
+        .or_else(|_| {
+            collider_parents
+                .get(grounded.entity)
+                .and_then(|parent| surfaces.get(parent.get()))
+        })
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Footsteps and landings from the animation markers, by surface
+pub fn footstep_sounds(
+    mut marker_events: EventReader<AnimationMarkerReached>,
+    protagonist_query: Query<(&Transform, Option<&Grounded>), With<Protagonist>>,
+    surfaces: Query<&Surface>,
+    collider_parents: Query<&ColliderParent>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    let Ok((transform, grounded)) = protagonist_query.get_single() else {
+        return;
+    };
+
+    for event in marker_events.read() {
+        let surface = surface_under(grounded, &surfaces, &collider_parents);
+        let cue = match event.marker {
+            AnimationMarker::Footstep => SoundCue::Footstep(surface),
+            AnimationMarker::Landing => SoundCue::Landing(surface),
+            AnimationMarker::HandPlant => continue,
+        };
+        sounds.send(PlaySound { cue, position: transform.translation });
+    }
+}
+
+/// Portal whooshes and explosions
+pub fn game_event_sounds(
+    mut game_events: EventReader<GameEvent>,
+    mut explosions: EventReader<Explosion>,
+    protagonist_query: Query<&Transform, With<Protagonist>>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    for event in game_events.read() {
+        if let GameEvent::PortalTraversed(_) = event {
+            if let Ok(transform) = protagonist_query.get_single() {
+                sounds.send(PlaySound { cue: SoundCue::PortalWhoosh, position: transform.translation });
+            }
+        }
+    }
+
+    for explosion in explosions.read() {
+        sounds.send(PlaySound { cue: SoundCue::Explosion, position: explosion.position });
+    }
+}
+
+/// Turns `PlaySound` events into spatial one-shots
+pub fn play_sounds(
+    mut commands: Commands,
+    mut sounds: EventReader<PlaySound>,
+    bank: Option<Res<SoundBank>>,
+    environment: Res<AudioEnvironment>,
+) {
+    let Some(bank) = bank else {
+        sounds.clear();
+        return;
+    };
+
+    for sound in sounds.read() {
+        let Some(source) = bank.cues.get(&sound.cue) else {
+            continue;
+        };
+        let base_volume = sound.cue.volume();
+
+        commands.spawn((
+            AudioBundle {
+                source: source.clone(),
+                settings: PlaybackSettings::DESPAWN
+                    .with_spatial(true)
+                    .with_volume(Volume::new(base_volume * environment.muffle.volume))
+                    .with_speed(environment.muffle.speed),
+            },
+            TransformBundle::from_transform(Transform::from_translation(sound.position)),
+            CueSound { base_volume },
+        ));
+    }
+}
+
+/// Crossfades the ambient loops and keeps the muffle on sounds already playing
+pub fn update_ambience(
+    time: Res<Time>,
+    environment: Res<AudioEnvironment>,
+    ambient_loops: Query<(&AmbientLoop, &AudioSink)>,
+    cue_sounds: Query<(&CueSound, &SpatialAudioSink)>,
+) {
+    let step = AMBIENCE_VOLUME * time.delta_seconds() / AMBIENCE_FADE_SECS;
+
+    for (ambient_loop, sink) in &ambient_loops {
+        let target = if ambient_loop.0 == environment.ambience {
+            AMBIENCE_VOLUME * environment.muffle.volume
+        } else {
+            0.0
+        };
+        let volume = sink.volume();
+        sink.set_volume(volume + (target - volume).clamp(-step, step));
+        sink.set_speed(environment.muffle.speed);
+    }
+
+    if environment.is_changed() {
+        for (cue, sink) in &cue_sounds {
+            sink.set_volume(cue.base_volume * environment.muffle.volume);
+            sink.set_speed(environment.muffle.speed);
+        }
+    }
+}
@@ -0,0 +1,202 @@
+// Colliders for imported glTF scenes. Convex decompositions collide much better than
+// trimeshes but take a while to work out, so they're baked once and cached next to
+// the model, keyed by a hash of each mesh. A node can pick its own collider with
+// glTF extras, `{"collider": "trimesh"}`, which also applies to everything under it.
+
+use bevy::{
+    gltf::{GltfExtras, GltfMeshExtras},
+    prelude::*,
+    scene::SceneInstance,
+};
+use avian3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::level::asset_path;
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ColliderKind {
+    Trimesh,
+    ConvexHull,
+    // Several convex hulls, baked and cached
+    Decomposition,
+    None,
+}
+
+/// Gives every mesh in a glTF scene a collider once the scene has spawned, then
+/// removes itself
+#[derive(Component, Debug, Clone)]
+pub struct BakedColliderHierarchy {
+    // The scene's file, the cache goes next to it
+    pub asset: &'static str,
+    // For nodes without a `collider` in their extras
+    pub default: ColliderKind,
+}
+
+impl BakedColliderHierarchy {
+    pub fn new(asset: &'static str) -> Self {
+        Self {
+            asset,
+            default: ColliderKind::Decomposition,
+        }
+    }
+}
+
+// Hull points for each mesh hash, in the mesh's own space
+#[derive(Serialize, Deserialize, Default)]
+struct ColliderCache {
+    hulls: BTreeMap<String, Vec<Vec<[f32; 3]>>>,
+}
+
+#[derive(Deserialize)]
+struct ColliderExtras {
+    collider: Option<ColliderKind>,
+}
+
+// `models/starhopper.glb` caches to `models/starhopper.glb.colliders.ron`
+fn cache_path(asset: &str) -> PathBuf {
+    let mut path = asset_path(asset).into_os_string();
+    path.push(".colliders.ron");
+    path.into()
+}
+
+fn load_cache(path: &Path) -> ColliderCache {
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return ColliderCache::default();
+    };
+    ron::from_str(&text).unwrap_or_else(|err| {
+        warn!("Couldn't parse {:?}, baking again: {}", path, err);
+        ColliderCache::default()
+    })
+}
+
+fn save_cache(path: &Path, cache: &ColliderCache) -> Result<(), String> {
+    let text = ron::ser::to_string_pretty(cache, ron::ser::PrettyConfig::default()).map_err(|err| err.to_string())?;
+    if let Some(folder) = path.parent() {
+        std::fs::create_dir_all(folder).map_err(|err| err.to_string())?;
+    }
+    std::fs::write(path, text).map_err(|err| err.to_string())
+}
+
+fn kind_in(extras: &str) -> Option<ColliderKind> {
+    serde_json::from_str::<ColliderExtras>(extras).ok()?.collider
+}
+
+// FNV-1a over the vertices and triangles, so the same mesh hashes the same on every build
+fn mesh_hash(vertices: &[Vec3], triangles: &[[u32; 3]]) -> u64 {
+    let bits = vertices
+        .iter()
+        .flat_map(|vertex| vertex.to_array().map(f32::to_bits))
+        .chain(triangles.iter().flatten().copied());
+    bits.flat_map(u32::to_le_bytes)
+        .fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+// Points of each convex part of a decomposition
+fn hull_points(collider: &Collider) -> Vec<Vec<[f32; 3]>> {
+    let Some(compound) = collider.shape().as_compound() else {
+        return Vec::new();
+    };
+    compound
+        .shapes()
+        .iter()
+        .filter_map(|(isometry, shape)| {
+            let hull = shape.as_convex_polyhedron()?;
+            Some(hull.points().iter().map(|point| (isometry * point).coords.into()).collect())
+        })
+        .collect()
+}
+
+/// The cached decomposition for `mesh`, baking and caching it if there isn't one.
+/// Returns the collider and whether it was baked.
+fn decomposition(mesh: &Mesh, cache: &mut ColliderCache) -> Option<(Collider, bool)> {
+    let vertices: Vec<Vec3> = mesh
+        .attribute(Mesh::ATTRIBUTE_POSITION)?
+        .as_float3()?
+        .iter()
+        .map(|&position| Vec3::from(position))
+        .collect();
+    let indices: Vec<u32> = match mesh.indices() {
+        Some(indices) => indices.iter().map(|index| index as u32).collect(),
+        None => (0..vertices.len() as u32).collect(),
+    };
+    let triangles: Vec<[u32; 3]> = indices.chunks_exact(3).map(|triangle| [triangle[0], triangle[1], triangle[2]]).collect();
+
+    let key = format!("{:016x}", mesh_hash(&vertices, &triangles));
+    let is_baked = !cache.hulls.contains_key(&key);
+    let hulls = cache
+        .hulls
+        .entry(key)
+        .or_insert_with(|| hull_points(&Collider::convex_decomposition(vertices, triangles)));
+
+    let parts: Vec<_> = hulls
+        .iter()
+        .filter_map(|points| Collider::convex_hull(points.iter().map(|&point| Vec3::from(point)).collect()))
+        .map(|hull| (Vec3::ZERO, Quat::IDENTITY, hull))
+        .collect();
+    (!parts.is_empty()).then(|| (Collider::compound(parts), is_baked))
+}
+
+/// Builds the colliders for each `BakedColliderHierarchy` whose scene is ready
+#[allow(clippy::too_many_arguments)]
+pub fn bake_scene_colliders(
+    mut commands: Commands,
+    scene_spawner: Res<SceneSpawner>,
+    meshes: Res<Assets<Mesh>>,
+    roots: Query<(Entity, &BakedColliderHierarchy, &SceneInstance)>,
+    children: Query<&Children>,
+    parents: Query<&Parent>,
+    mesh_handles: Query<&Handle<Mesh>>,
+    extras: Query<&GltfExtras>,
+    mesh_extras: Query<&GltfMeshExtras>,
+) {
+    for (root, hierarchy, instance) in &roots {
+        if !scene_spawner.instance_is_ready(**instance) {
+            continue;
+        }
+
+        let path = cache_path(hierarchy.asset);
+        let mut cache = load_cache(&path);
+        let mut baked = 0;
+
+        for entity in children.iter_descendants(root) {
+            let Some(mesh) = mesh_handles.get(entity).ok().and_then(|handle| meshes.get(handle)) else {
+                continue;
+            };
+            // The closest node up the tree that says, the primitive's own mesh extras first
+            let kind = std::iter::once(entity)
+                .chain(parents.iter_ancestors(entity))
+                .find_map(|node| {
+                    let own = mesh_extras.get(node).ok().and_then(|extras| kind_in(&extras.value));
+                    own.or_else(|| extras.get(node).ok().and_then(|extras| kind_in(&extras.value)))
+                })
+                .unwrap_or(hierarchy.default);
+
+            let collider = match kind {
+                ColliderKind::None => continue,
+                ColliderKind::Trimesh => Collider::trimesh_from_mesh(mesh),
+                ColliderKind::ConvexHull => Collider::convex_hull_from_mesh(mesh),
+                ColliderKind::Decomposition => decomposition(mesh, &mut cache).map(|(collider, is_baked)| {
+                    baked += is_baked as usize;
+                    collider
+                }),
+            };
+            match collider {
+                Some(collider) => {
+                    commands.entity(entity).insert(collider);
+                }
+                None => warn!("Couldn't make a {:?} collider for a mesh in {}", kind, hierarchy.asset),
+            }
+        }
+
+        if baked > 0 {
+            match save_cache(&path, &cache) {
+                Ok(()) => info!("Baked {} convex decompositions into {:?}", baked, path),
+                Err(err) => warn!("Couldn't write {:?}: {}", path, err),
+            }
+        }
+        commands.entity(root).remove::<BakedColliderHierarchy>();
+    }
+}
@@ -0,0 +1,235 @@
+use bevy::prelude::*;
+use avian3d::prelude::*;
+use serde::Deserialize;
+use crate::components::Protagonist;
+use crate::level::read_ron_asset;
+use crate::resources::{Animations, PROTAGONIST_GLB, SCENES};
+use crate::systems::climb::ClimbState;
+use crate::systems::health::Dead;
+use crate::systems::protagonist::Grounded;
+use crate::systems::stance::Stance;
+use crate::systems::swim::Swimming;
+
+pub const BLEND_SPACES_PATH: &str = "animations/blend_spaces.ron";
+
+// How long it takes to fade between the blend spaces and the one-off clips
+const ENGAGE_SECS: f32 = 0.25;
+// Slower than this counts as standing still
+const MIN_MOVING_SPEED: f32 = 0.1;
+
+// A clip placed on the speed line
+#[derive(Deserialize, Debug, Clone)]
+pub struct SpeedSample {
+    pub clip: String,
+    // Metres per second this clip matches
+    pub speed: f32,
+    #[serde(default = "default_playback")]
+    pub playback: f32,
+}
+
+// A clip placed on the strafing plane
+#[derive(Deserialize, Debug, Clone)]
+pub struct DirectionSample {
+    pub clip: String,
+    // Velocity this clip matches, x to the right and y forward, in metres per second
+    pub velocity: (f32, f32),
+    #[serde(default = "default_playback")]
+    pub playback: f32,
+}
+
+fn default_playback() -> f32 {
+    1.0
+}
+
+/// Blend space layout, read from `assets/animations/blend_spaces.ron`
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct BlendSpacesConfig {
+    pub speed: Vec<SpeedSample>,
+    pub strafe: Vec<DirectionSample>,
+    // One-off clips the blend spaces take over from when they'd be played standing up
+    pub replaces: Vec<String>,
+}
+
+/// Blend nodes in the animation graph. Under `locomotion` the speed blend covers
+/// moving straight ahead and the strafe blend everything else.
+#[derive(Resource)]
+pub struct BlendSpaces {
+    locomotion: AnimationNodeIndex,
+    speed_node: AnimationNodeIndex,
+    strafe_node: AnimationNodeIndex,
+    // Sorted by speed
+    speed: Vec<(f32, AnimationNodeIndex)>,
+    strafe: Vec<(Vec2, AnimationNodeIndex)>,
+    // Every clip under the blend nodes, with its playback speed
+    clips: Vec<(AnimationNodeIndex, f32)>,
+    // The flat clip nodes the blend takes over from
+    replaces: Vec<AnimationNodeIndex>,
+}
+
+fn load_clip(asset_server: &AssetServer, name: &str) -> Option<Handle<AnimationClip>> {
+    let Some(&index) = SCENES.get(name) else {
+        warn!("Blend space clip {} isn't in SCENES", name);
+        return None;
+    };
+    Some(asset_server.load(GltfAssetLabel::Animation(index).from_asset(PROTAGONIST_GLB)))
+}
+
+impl BlendSpaces {
+    /// Adds the blend nodes to `graph`. The locomotion node starts switched off.
+    pub fn build(
+        path: &str,
+        graph: &mut AnimationGraph,
+        animations: &[AnimationNodeIndex],
+        asset_server: &AssetServer,
+    ) -> Self {
+        let config: BlendSpacesConfig = read_ron_asset(path).unwrap_or_default();
+
+        let locomotion = graph.add_blend(0.0, graph.root);
+        let speed_node = graph.add_blend(1.0, locomotion);
+        let strafe_node = graph.add_blend(0.0, locomotion);
+        let mut clips = Vec::new();
+
+        let mut speed: Vec<(f32, AnimationNodeIndex)> = config
+            .speed
+            .iter()
+            .filter_map(|sample| {
+                let node = graph.add_clip(load_clip(asset_server, &sample.clip)?, 0.0, speed_node);
+                clips.push((node, sample.playback));
+                Some((sample.speed, node))
+            })
+            .collect();
+        speed.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let strafe = config
+            .strafe
+            .iter()
+            .filter_map(|sample| {
+                let node = graph.add_clip(load_clip(asset_server, &sample.clip)?, 0.0, strafe_node);
+                clips.push((node, sample.playback));
+                Some((Vec2::new(sample.velocity.0, sample.velocity.1), node))
+            })
+            .collect();
+
+        let replaces = config
+            .replaces
+            .iter()
+            .filter_map(|name| SCENES.get(name.as_str()).map(|&index| animations[index]))
+            .collect();
+
+        Self {
+            locomotion,
+            speed_node,
+            strafe_node,
+            speed,
+            strafe,
+            clips,
+            replaces,
+        }
+    }
+
+    /// Starts every blend space clip looping, the graph weights decide what's heard from
+    pub fn start(&self, player: &mut AnimationPlayer) {
+        for &(node, playback) in &self.clips {
+            player.play(node).repeat().set_speed(playback);
+        }
+    }
+
+    // Linear between the two samples either side of `speed`
+    fn speed_weights(&self, speed: f32) -> Vec<(AnimationNodeIndex, f32)> {
+        let mut weights: Vec<_> = self.speed.iter().map(|&(_, node)| (node, 0.0)).collect();
+        if weights.is_empty() {
+            return weights;
+        }
+
+        let above = self.speed.partition_point(|&(sample, _)| sample <= speed);
+        if above == 0 {
+            weights[0].1 = 1.0;
+        } else if above == self.speed.len() {
+            weights[above - 1].1 = 1.0;
+        } else {
+            let (low, high) = (self.speed[above - 1].0, self.speed[above].0);
+            let t = (speed - low) / (high - low);
+            weights[above - 1].1 = 1.0 - t;
+            weights[above].1 = t;
+        }
+        weights
+    }
+
+    // Inverse distance weighting, exact on a sample
+    fn strafe_weights(&self, velocity: Vec2) -> Vec<(AnimationNodeIndex, f32)> {
+        if let Some(&(_, node)) = self.strafe.iter().find(|(sample, _)| sample.distance_squared(velocity) < 1e-4) {
+            return self.strafe.iter().map(|&(_, other)| (other, if other == node { 1.0 } else { 0.0 })).collect();
+        }
+
+        let raw: Vec<_> = self
+            .strafe
+            .iter()
+            .map(|&(sample, node)| (node, 1.0 / sample.distance_squared(velocity)))
+            .collect();
+        let total: f32 = raw.iter().map(|(_, weight)| weight).sum();
+        raw.into_iter().map(|(node, weight)| (node, weight / total)).collect()
+    }
+}
+
+/// Keeps the blend weights in step with the protagonist's velocity, and fades between
+/// the blend spaces and whatever one-off clip `AnimationTransitions` is playing
+#[allow(clippy::type_complexity)]
+pub fn update_blend_spaces(
+    time: Res<Time>,
+    blend_spaces: Res<BlendSpaces>,
+    animations: Res<Animations>,
+    mut graphs: ResMut<Assets<AnimationGraph>>,
+    protagonist_query: Query<
+        (&Transform, &LinearVelocity, &Stance, Has<Grounded>, Has<Swimming>, Has<ClimbState>, Has<Dead>),
+        With<Protagonist>,
+    >,
+    animation_players: Query<&AnimationTransitions>,
+    mut engaged: Local<f32>,
+) {
+    let Ok((transform, velocity, stance, is_grounded, is_swimming, is_climbing, is_dead)) = protagonist_query.get_single() else {
+        return;
+    };
+    let Some(graph) = graphs.get_mut(&animations.graph) else {
+        return;
+    };
+
+    for transitions in &animation_players {
+        let main = transitions.get_main_animation();
+        let wanted = is_grounded
+            && *stance == Stance::Standing
+            && !is_swimming
+            && !is_climbing
+            && !is_dead
+            && main.is_some_and(|main| blend_spaces.replaces.contains(&main));
+
+        let step = time.delta_seconds() / ENGAGE_SECS;
+        *engaged = if wanted { (*engaged + step).min(1.0) } else { (*engaged - step).max(0.0) };
+    }
+
+    // Velocity in the protagonist's own frame, x to the right and y forward
+    let local = transform.rotation.inverse() * velocity.0;
+    let planar = Vec2::new(local.x, -local.z);
+    let speed = planar.length();
+
+    // All speed blend going straight ahead, all strafe blend going sideways or back
+    let ahead = if speed < MIN_MOVING_SPEED { 1.0 } else { (planar.y / speed).max(0.0).powi(2) };
+
+    let mut set_weight = |node: AnimationNodeIndex, weight: f32| {
+        if let Some(graph_node) = graph.get_mut(node) {
+            graph_node.weight = weight;
+        }
+    };
+    // The flat clips get whatever the blend spaces don't. Their own weights belong to
+    // `AnimationTransitions`, which resets them every frame, so this goes on their parent.
+    set_weight(animations.flat_clips, 1.0 - *engaged);
+    set_weight(blend_spaces.locomotion, *engaged);
+    set_weight(blend_spaces.speed_node, ahead);
+    set_weight(blend_spaces.strafe_node, 1.0 - ahead);
+    for (node, weight) in blend_spaces.speed_weights(speed) {
+        set_weight(node, weight);
+    }
+    for (node, weight) in blend_spaces.strafe_weights(planar) {
+        set_weight(node, weight);
+    }
+}
@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 use crate::components::Protagonist;
 
+// Where the camera sits relative to the protagonist, slightly above and behind
+pub const CAMERA_FOLLOW_OFFSET: Vec3 = Vec3::new(0.0, 3.0, 10.0);
+
 pub fn rotate_camera(
     time: Res<Time>,
     protagonist_query: Query<&Transform, With<Protagonist>>, // Immutable access to protagonist
@@ -11,11 +14,8 @@ pub fn rotate_camera(
         let protagonist_rotation = protagonist_transform.rotation;
 
         for mut camera_transform in camera_query.iter_mut() {
-            // Define the desired offset for the camera (relative to the protagonist)
-            let follow_offset = Vec3::new(0.0, 3.0, 10.0); // Slightly above and behind the protagonist
-
             // Calculate the new camera position by applying the protagonist's rotation to the offset
-            let rotated_offset = protagonist_rotation * follow_offset;
+            let rotated_offset = protagonist_rotation * CAMERA_FOLLOW_OFFSET;
             let new_camera_position = protagonist_position + rotated_offset;
 
             // Smoothly move the camera to the new position
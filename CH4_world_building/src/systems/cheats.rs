@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+use crate::components::Protagonist;
+use crate::resources::{Animations, SCENES};
+
+use std::time::Duration;
+
+/// V drops the protagonist 10 units, B lifts it 15, Tab steps through every animation clip
+pub fn cheat_keys(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut protagonist_query: Query<&mut Transform, With<Protagonist>>,
+    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+    animations: Res<Animations>,
+    mut current_animation: Local<usize>,
+) {
+    for mut protagonist_transform in &mut protagonist_query {
+        // Teleport the character 10 units down when V is pressed
+        if keyboard_input.just_pressed(KeyCode::KeyV) {
+            protagonist_transform.translation.y -= 10.0;
+            info!("Teleported 10 units down!");
+        }
+
+        // Teleport the character 15 units up when B is pressed
+        if keyboard_input.just_pressed(KeyCode::KeyB) {
+            protagonist_transform.translation.y += 15.0;
+            info!("Teleported 15 units up!");
+        }
+    }
+
+    // Switch animations with Tab
+    if keyboard_input.just_pressed(KeyCode::Tab) {
+        *current_animation = (*current_animation + 1) % animations.animations.len();
+        let key = SCENES
+            .iter()
+            .find_map(|(k, &v)| if v == *current_animation { Some(k) } else { None });
+        info!(
+            "Scene {}: {}",
+            *current_animation,
+            key.unwrap_or(&"* DISCARDED ANIMATION*")
+        );
+
+        for (mut player, mut transitions) in &mut animation_players {
+            transitions
+                .play(
+                    &mut player,
+                    animations.animations[*current_animation],
+                    Duration::from_millis(250),
+                )
+                .repeat();
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use avian3d::prelude::*;
+use crate::components::Protagonist;
+use crate::level::LevelData;
+use crate::systems::health::RespawnPoint;
+
+// Where the level wants the protagonist to start
+#[derive(Component)]
+pub struct PlayerStart;
+
+// Sensor volume that becomes the respawn point when touched
+#[derive(Component)]
+pub struct Checkpoint {
+    pub name: String,
+    pub spawn: Transform,
+}
+
+#[derive(Event)]
+pub struct CheckpointReached {
+    pub name: String,
+}
+
+// Names of the checkpoints touched so far, oldest first
+#[derive(Resource, Default)]
+pub struct CheckpointProgress {
+    pub reached: Vec<String>,
+}
+
+impl CheckpointProgress {
+    pub fn latest(&self) -> Option<&str> {
+        self.reached.last().map(String::as_str)
+    }
+}
+
+/// Spawns the player start marker and checkpoint sensors declared by the level
+pub fn spawn_level_markers(commands: &mut Commands, level: &LevelData) {
+    commands.spawn((
+        PlayerStart,
+        TransformBundle::from_transform(level.player_start.transform()),
+        Name::new("PlayerStart"),
+    ));
+
+    for checkpoint in &level.checkpoints {
+        let spawn = checkpoint.spawn.transform();
+        let [x, y, z] = checkpoint.size;
+
+        commands.spawn((
+            RigidBody::Static,
+            Collider::cuboid(x, y, z),
+            Sensor,
+            Checkpoint {
+                name: checkpoint.name.clone(),
+                spawn,
+            },
+            TransformBundle::from_transform(spawn),
+            Name::new(format!("Checkpoint {}", checkpoint.name)),
+        ));
+    }
+}
+
+pub fn checkpoint_system(
+    mut collision_events: EventReader<CollisionStarted>,
+    protagonist_query: Query<Entity, With<Protagonist>>,
+    checkpoint_query: Query<&Checkpoint>,
+    mut respawn_point: ResMut<RespawnPoint>,
+    mut progress: ResMut<CheckpointProgress>,
+    mut reached_events: EventWriter<CheckpointReached>,
+) {
+    let Ok(protagonist) = protagonist_query.get_single() else {
+        return;
+    };
+
+    for CollisionStarted(e1, e2) in collision_events.read() {
+        let other = if *e1 == protagonist {
+            *e2
+        } else if *e2 == protagonist {
+            *e1
+        } else {
+            continue;
+        };
+
+        let Ok(checkpoint) = checkpoint_query.get(other) else {
+            continue;
+        };
+
+        // Walking back through an older checkpoint makes it the latest again
+        respawn_point.0 = checkpoint.spawn;
+        if progress.latest() != Some(checkpoint.name.as_str()) {
+            info!("Reached checkpoint {}", checkpoint.name);
+            progress.reached.push(checkpoint.name.clone());
+            reached_events.send(CheckpointReached {
+                name: checkpoint.name.clone(),
+            });
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use bevy::{
+    animation::RepeatAnimation,
+    prelude::*,
+};
+use avian3d::prelude::*;
+use crate::components::Protagonist;
+use crate::resources::{Animations, SCENES};
+use crate::systems::actions::{Action, ActionState};
+use crate::systems::health::Dead;
+use crate::systems::input::FallingState;
+use crate::systems::stance::Stance;
+
+use std::time::Duration;
+
+// Height above the feet the forward probe starts at
+const CHEST_HEIGHT: f32 = 1.2;
+// How far in front of the protagonist a wall can be and still be grabbed
+const WALL_REACH: f32 = 0.8;
+// Radius of the spheres used for the forward and downward probes
+const PROBE_RADIUS: f32 = 0.2;
+// How far past the wall face the downward probe looks for the top
+const LEDGE_DEPTH: f32 = 0.3;
+// Ledge tops have to be within this range above the feet to be grabbed
+const MIN_GRAB_HEIGHT: f32 = 0.8;
+const MAX_GRAB_HEIGHT: f32 = 2.6;
+// Where the body hangs relative to the ledge edge
+const HANG_DEPTH: f32 = 1.8;
+const HANG_WALL_OFFSET: f32 = 0.4;
+const SHIMMY_SPEED: f32 = 1.5;
+const MANTLE_SECS: f32 = 1.2;
+
+// Colliders opt in to being climbed with this. On hierarchies (like the starship
+// scene) it goes on the rigid body, child colliders inherit it.
+#[derive(Component)]
+pub struct Climbable;
+
+// A ledge found by the probes, all in world space
+#[derive(Clone, Copy, Debug)]
+pub struct Ledge {
+    // Point on the top edge of the wall
+    pub point: Vec3,
+    // Wall normal, pointing back towards the climber
+    pub normal: Vec3,
+}
+
+impl Ledge {
+    fn hang_position(&self) -> Vec3 {
+        self.point + self.normal * HANG_WALL_OFFSET - Vec3::Y * HANG_DEPTH
+    }
+
+    // Facing the wall
+    fn hang_rotation(&self) -> Quat {
+        Quat::from_rotation_y(self.normal.x.atan2(self.normal.z))
+    }
+
+    fn mantle_target(&self) -> Vec3 {
+        self.point - self.normal * (HANG_WALL_OFFSET + PROBE_RADIUS) + Vec3::Y * 0.2
+    }
+}
+
+// Present while the protagonist is on a ledge, normal movement is off until it's removed
+#[derive(Component)]
+pub enum ClimbState {
+    Hanging(Ledge),
+    Mantling {
+        start: Vec3,
+        target: Vec3,
+        timer: Timer,
+    },
+}
+
+fn is_climbable(
+    entity: Entity,
+    climbables: &Query<(), With<Climbable>>,
+    collider_parents: &Query<&ColliderParent>,
+) -> bool {
+    climbables.contains(entity)
+        || collider_parents
+            .get(entity)
+            .is_ok_and(|parent| climbables.contains(parent.get()))
+}
+
+/// Looks for a climbable ledge in front of `feet` along `forward`:
+/// a forward probe to find the wall, then a downward probe to find its top.
+fn find_ledge(
+    spatial_query: &SpatialQuery,
+    climber: Entity,
+    feet: Vec3,
+    forward: Dir3,
+    climbables: &Query<(), With<Climbable>>,
+    collider_parents: &Query<&ColliderParent>,
+) -> Option<Ledge> {
+    let filter = SpatialQueryFilter::from_excluded_entities([climber]);
+    let probe = Collider::sphere(PROBE_RADIUS);
+    let chest = feet + Vec3::Y * CHEST_HEIGHT;
+
+    let wall_hit = spatial_query.cast_shape(&probe, chest, Quat::IDENTITY, forward, WALL_REACH, true, filter.clone())?;
+    if !is_climbable(wall_hit.entity, climbables, collider_parents) {
+        return None;
+    }
+
+    // A ray at the same height gives us the wall normal in world space
+    let wall_ray = spatial_query.cast_ray(chest, forward, WALL_REACH + PROBE_RADIUS, true, filter.clone())?;
+    let normal = Vec3::new(wall_ray.normal.x, 0.0, wall_ray.normal.z).normalize_or_zero();
+    if normal == Vec3::ZERO {
+        return None;
+    }
+    let wall_face = chest + *forward * wall_ray.time_of_impact;
+
+    // Drop a probe just past the wall face from above the highest grabbable point
+    let probe_top = feet.y + MAX_GRAB_HEIGHT + PROBE_RADIUS * 2.0;
+    let down_origin = Vec3::new(wall_face.x, probe_top, wall_face.z) - normal * LEDGE_DEPTH;
+    let top_hit = spatial_query.cast_shape(
+        &probe,
+        down_origin,
+        Quat::IDENTITY,
+        Dir3::NEG_Y,
+        MAX_GRAB_HEIGHT - MIN_GRAB_HEIGHT + PROBE_RADIUS * 2.0,
+        true,
+        filter,
+    )?;
+
+    // Started inside something, the wall goes higher than we can reach
+    if top_hit.time_of_impact <= 0.0 {
+        return None;
+    }
+
+    let top_y = down_origin.y - top_hit.time_of_impact - PROBE_RADIUS;
+    let height = top_y - feet.y;
+    if !(MIN_GRAB_HEIGHT..=MAX_GRAB_HEIGHT).contains(&height) {
+        return None;
+    }
+
+    Some(Ledge {
+        point: Vec3::new(wall_face.x, top_y, wall_face.z),
+        normal,
+    })
+}
+
+fn play_climb(player: &mut AnimationPlayer, transitions: &mut AnimationTransitions, animations: &Animations) {
+    let climb = *SCENES.get("CLIMB").unwrap();
+    transitions
+        .play(player, animations.animations[climb], Duration::from_millis(150))
+        .set_repeat(RepeatAnimation::Never);
+}
+
+/// Grabs a ledge when walking or falling into a climbable wall with a reachable top
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn grab_ledges(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    spatial_query: SpatialQuery,
+    mut protagonist_query: Query<
+        (Entity, &mut Transform, &mut LinearVelocity, &mut FallingState),
+        (With<Protagonist>, Without<ClimbState>, Without<Dead>),
+    >,
+    climbables: Query<(), With<Climbable>>,
+    collider_parents: Query<&ColliderParent>,
+    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+    animations: Res<Animations>,
+) {
+    if !actions.pressed(Action::Forward) {
+        return;
+    }
+
+    let Ok((entity, mut transform, mut velocity, mut falling_state)) = protagonist_query.get_single_mut() else {
+        return;
+    };
+
+    let Ok(forward) = Dir3::new(-(transform.rotation * Vec3::Z).with_y(0.0)) else {
+        return;
+    };
+
+    let Some(ledge) = find_ledge(&spatial_query, entity, transform.translation, forward, &climbables, &collider_parents) else {
+        return;
+    };
+
+    dev_log!("Grabbed ledge at {:?}", ledge.point);
+    transform.translation = ledge.hang_position();
+    transform.rotation = ledge.hang_rotation();
+    velocity.0 = Vec3::ZERO;
+    // Catching the ledge cancels the fall
+    falling_state.peak_fall_speed = 0.0;
+
+    commands
+        .entity(entity)
+        .insert((ClimbState::Hanging(ledge), GravityScale(0.0)));
+
+    // Hold the first frame of the climb while hanging
+    for (mut player, mut transitions) in &mut animation_players {
+        play_climb(&mut player, &mut transitions, &animations);
+        player.pause_all();
+    }
+}
+
+/// Shimmy, mantle or let go while on a ledge
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn climb_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    spatial_query: SpatialQuery,
+    mut climber_query: Query<
+        (Entity, &mut ClimbState, &mut Transform, &mut LinearVelocity, &mut AngularVelocity, &Stance),
+        With<Protagonist>,
+    >,
+    climbables: Query<(), With<Climbable>>,
+    collider_parents: Query<&ColliderParent>,
+    sensors: Query<(), With<Sensor>>,
+    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+    animations: Res<Animations>,
+) {
+    let Ok((entity, mut state, mut transform, mut velocity, mut angular_velocity, stance)) = climber_query.get_single_mut() else {
+        return;
+    };
+
+    // Gravity is off while climbing, but contacts can still nudge and spin the body
+    velocity.0 = Vec3::ZERO;
+    angular_velocity.0 = Vec3::ZERO;
+
+    let next_state = match &mut *state {
+        ClimbState::Hanging(ledge) => {
+            if actions.just_pressed(Action::Backward) || actions.just_pressed(Action::Jump) {
+                dev_log!("Let go of ledge");
+                commands.entity(entity).remove::<(ClimbState, GravityScale)>();
+                return;
+            }
+
+            if actions.just_pressed(Action::Forward) {
+                // Only climb up if there's room on top in the current stance
+                let blocked = spatial_query
+                    .shape_intersections(
+                        &stance.collider(),
+                        ledge.mantle_target() + Vec3::Y * 0.2,
+                        transform.rotation,
+                        SpatialQueryFilter::from_excluded_entities([entity]),
+                    )
+                    .iter()
+                    .any(|&hit| !sensors.contains(hit));
+
+                if blocked {
+                    dev_log!("No room to climb up");
+                    None
+                } else {
+                    for (mut player, mut transitions) in &mut animation_players {
+                        play_climb(&mut player, &mut transitions, &animations);
+                        player.resume_all();
+                    }
+                    Some(ClimbState::Mantling {
+                        start: transform.translation,
+                        target: ledge.mantle_target(),
+                        timer: Timer::from_seconds(MANTLE_SECS, TimerMode::Once),
+                    })
+                }
+            } else {
+                // Shimmy along the edge, staying put if the ledge runs out
+                let mut shimmy = 0.0;
+                if actions.pressed(Action::TurnLeft) {
+                    shimmy -= 1.0;
+                }
+                if actions.pressed(Action::TurnRight) {
+                    shimmy += 1.0;
+                }
+
+                if shimmy != 0.0 {
+                    // Right, as seen by someone facing the wall, so facing against the normal
+                    let right = Vec3::Y.cross(ledge.normal);
+                    let step = right * shimmy * SHIMMY_SPEED * time.delta_seconds();
+                    let feet = ledge.hang_position() + step;
+                    let forward = Dir3::new(-ledge.normal).unwrap_or(Dir3::NEG_Z);
+
+                    // Re-probe from where the feet would be if standing on the hang height
+                    let probe_feet = Vec3::new(feet.x, ledge.point.y - (MIN_GRAB_HEIGHT + MAX_GRAB_HEIGHT) / 2.0, feet.z)
+                        + ledge.normal * (HANG_WALL_OFFSET - PROBE_RADIUS);
+                    if let Some(next) = find_ledge(&spatial_query, entity, probe_feet, forward, &climbables, &collider_parents) {
+                        *ledge = next;
+                        transform.translation = ledge.hang_position();
+                        transform.rotation = ledge.hang_rotation();
+                    }
+                }
+                None
+            }
+        }
+        ClimbState::Mantling { start, target, timer } => {
+            timer.tick(time.delta());
+            let t = timer.fraction();
+            // Up first, then over the edge
+            let rise = (t * 2.0).min(1.0);
+            let over = ((t - 0.5) * 2.0).clamp(0.0, 1.0);
+            transform.translation = Vec3::new(
+                start.x + (target.x - start.x) * over,
+                start.y + (target.y - start.y) * rise,
+                start.z + (target.z - start.z) * over,
+            );
+
+            if timer.finished() {
+                dev_log!("Climbed up to {:?}", transform.translation);
+                commands.entity(entity).remove::<(ClimbState, GravityScale)>();
+            }
+            None
+        }
+    };
+
+    if let Some(next_state) = next_state {
+        *state = next_state;
+    }
+}
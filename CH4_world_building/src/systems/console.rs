@@ -0,0 +1,309 @@
+use bevy::{
+    ecs::system::SystemId,
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+};
+use crate::level::asset_path;
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+// Run line by line at startup, same syntax as typing into the console. `#` starts a comment.
+pub const STARTUP_SCRIPT_PATH: &str = "config/startup.console";
+
+const CONSOLE_KEY: KeyCode = KeyCode::Backquote;
+const LOG_LINES: usize = 14;
+
+pub struct ConsoleCommand {
+    pub help: String,
+    // What tab completion offers for the first argument
+    pub completions: Vec<String>,
+    system: SystemId<Vec<String>>,
+}
+
+/// Every command the console knows, by name
+#[derive(Resource, Default)]
+pub struct ConsoleCommands {
+    commands: BTreeMap<String, ConsoleCommand>,
+}
+
+impl ConsoleCommands {
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &ConsoleCommand)> {
+        self.commands.iter()
+    }
+
+    /// Completes the word being typed at the end of `line`. Returns the new line and,
+    /// if there's more than one way to go, the candidates.
+    pub fn complete(&self, line: &str) -> (String, Vec<String>) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let typing_new_word = line.is_empty() || line.ends_with(' ');
+        let (done, partial) = if typing_new_word {
+            (&words[..], "")
+        } else {
+            (&words[..words.len() - 1], words[words.len() - 1])
+        };
+
+        let options: Vec<&String> = match done {
+            [] => self.commands.keys().collect(),
+            [name] => self.commands.get(*name).map_or(Vec::new(), |command| command.completions.iter().collect()),
+            _ => Vec::new(),
+        };
+        let candidates: Vec<String> = options
+            .into_iter()
+            .filter(|option| option.to_lowercase().starts_with(&partial.to_lowercase()))
+            .cloned()
+            .collect();
+
+        let prefix = done.iter().map(|word| format!("{} ", word)).collect::<String>();
+        match candidates.as_slice() {
+            [] => (line.to_string(), Vec::new()),
+            [only] => (format!("{}{} ", prefix, only), Vec::new()),
+            _ => {
+                // Keeps what was typed if the shared part is shorter, which happens with different case
+                let common = common_prefix(&candidates);
+                let word = if common.len() >= partial.len() { common } else { partial.to_string() };
+                (format!("{}{}", prefix, word), candidates)
+            }
+        }
+    }
+}
+
+fn common_prefix(words: &[String]) -> String {
+    let first = &words[0];
+    let length = words[1..].iter().fold(first.len(), |length, word| {
+        first
+            .chars()
+            .zip(word.chars())
+            .take_while(|(a, b)| a == b)
+            .count()
+            .min(length)
+    });
+    first.chars().take(length).collect()
+}
+
+/// Adding console commands, from `main` or any plugin's `build`
+pub trait ConsoleAppExt {
+    fn add_console_command<M>(
+        &mut self,
+        name: &str,
+        help: &str,
+        completions: &[&str],
+        system: impl IntoSystem<Vec<String>, (), M> + 'static,
+    ) -> &mut Self;
+}
+
+impl ConsoleAppExt for App {
+    fn add_console_command<M>(
+        &mut self,
+        name: &str,
+        help: &str,
+        completions: &[&str],
+        system: impl IntoSystem<Vec<String>, (), M> + 'static,
+    ) -> &mut Self {
+        let system = self.world_mut().register_system(system);
+        self.world_mut()
+            .get_resource_or_insert_with(ConsoleCommands::default)
+            .commands
+            .insert(
+                name.to_string(),
+                ConsoleCommand {
+                    help: help.to_string(),
+                    completions: completions.iter().map(|completion| completion.to_string()).collect(),
+                    system,
+                },
+            );
+        self
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Console {
+    pub open: bool,
+    input: String,
+    log: Vec<String>,
+    history: Vec<String>,
+    // Position while going back through the history with the arrow keys
+    history_index: Option<usize>,
+    // Lines waiting to run, typed or from the startup script
+    queue: Vec<String>,
+}
+
+impl Console {
+    /// Adds a line to the console, and the log
+    pub fn print(&mut self, line: impl Into<String>) {
+        let line = line.into();
+        info!("{}", line);
+        self.log.push(line);
+        if self.log.len() > LOG_LINES {
+            self.log.remove(0);
+        }
+    }
+
+    pub fn run(&mut self, line: impl Into<String>) {
+        self.queue.push(line.into());
+    }
+
+    fn clear(&mut self) {
+        self.log.clear();
+    }
+}
+
+/// Parses every argument as a `T`, or explains which one it couldn't
+pub fn parse_args<T: FromStr, const N: usize>(args: &[String]) -> Result<[T; N], String> {
+    if args.len() != N {
+        return Err(format!("Expected {} arguments, got {}", N, args.len()));
+    }
+    let parsed: Vec<T> = args
+        .iter()
+        .map(|arg| arg.parse().map_err(|_| format!("Can't read {:?}", arg)))
+        .collect::<Result<_, _>>()?;
+    parsed.try_into().map_err(|_| "Wrong number of arguments".to_string())
+}
+
+// Marker for the console's text
+#[derive(Component)]
+pub struct ConsoleText;
+
+pub fn setup_console(mut commands: Commands, mut console: ResMut<Console>) {
+    let mut text = TextBundle::from_section(
+        "",
+        TextStyle {
+            font_size: 18.0,
+            color: Color::WHITE,
+            ..default()
+        },
+    )
+    .with_style(Style {
+        position_type: PositionType::Absolute,
+        top: Val::Px(0.0),
+        left: Val::Px(0.0),
+        width: Val::Percent(100.0),
+        padding: UiRect::all(Val::Px(8.0)),
+        ..default()
+    })
+    .with_background_color(Color::srgba(0.0, 0.0, 0.0, 0.75));
+    text.visibility = Visibility::Hidden;
+    commands.spawn((text, ConsoleText));
+
+    // A missing script is fine, most setups don't have one
+    if let Ok(script) = std::fs::read_to_string(asset_path(STARTUP_SCRIPT_PATH)) {
+        info!("Running {}", STARTUP_SCRIPT_PATH);
+        for line in script.lines() {
+            console.run(line);
+        }
+    }
+}
+
+/// Opens and closes the console with the backquote key, and handles typing while it's open
+pub fn console_input(
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut console: ResMut<Console>,
+    registry: Res<ConsoleCommands>,
+) {
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        if event.key_code == CONSOLE_KEY {
+            console.open = !console.open;
+            continue;
+        }
+        if !console.open {
+            continue;
+        }
+
+        match &event.logical_key {
+            Key::Enter => {
+                let line = std::mem::take(&mut console.input);
+                if !line.trim().is_empty() {
+                    console.history.push(line.clone());
+                }
+                console.history_index = None;
+                console.run(line);
+            }
+            Key::Escape => console.open = false,
+            Key::Backspace => {
+                console.input.pop();
+            }
+            Key::Tab => {
+                let (completed, candidates) = registry.complete(&console.input);
+                console.input = completed;
+                if !candidates.is_empty() {
+                    console.print(candidates.join("  "));
+                }
+            }
+            Key::ArrowUp | Key::ArrowDown => {
+                if console.history.is_empty() {
+                    continue;
+                }
+                let last = console.history.len() - 1;
+                let index = match (console.history_index, &event.logical_key) {
+                    (None, Key::ArrowUp) => Some(last),
+                    (Some(index), Key::ArrowUp) => Some(index.saturating_sub(1)),
+                    (Some(index), _) if index < last => Some(index + 1),
+                    _ => None,
+                };
+                console.history_index = index;
+                console.input = index.map_or(String::new(), |index| console.history[index].clone());
+            }
+            Key::Space => console.input.push(' '),
+            Key::Character(text) => console.input.push_str(text),
+            _ => {}
+        }
+    }
+}
+
+/// While typing, gameplay shouldn't see the keys
+pub fn block_game_input(console: Res<Console>, mut keyboard_input: ResMut<ButtonInput<KeyCode>>) {
+    if console.open {
+        keyboard_input.reset_all();
+    }
+}
+
+/// Runs whatever's queued up, each command as its own one-shot system
+pub fn run_console_commands(
+    mut commands: Commands,
+    mut console: ResMut<Console>,
+    registry: Res<ConsoleCommands>,
+) {
+    for line in std::mem::take(&mut console.queue) {
+        let line = line.split('#').next().unwrap_or_default().trim().to_string();
+        let mut words = line.split_whitespace().map(String::from);
+        let Some(name) = words.next() else {
+            continue;
+        };
+
+        console.print(format!("> {}", line));
+        match registry.commands.get(&name) {
+            Some(command) => commands.run_system_with_input(command.system, words.collect()),
+            None => console.print(format!("Unknown command {:?}, try help", name)),
+        }
+    }
+}
+
+pub fn update_console_text(console: Res<Console>, mut text_query: Query<(&mut Text, &mut Visibility), With<ConsoleText>>) {
+    if !console.is_changed() {
+        return;
+    }
+
+    for (mut text, mut visibility) in &mut text_query {
+        *visibility = if console.open { Visibility::Visible } else { Visibility::Hidden };
+        let mut lines = console.log.clone();
+        lines.push(format!("> {}_", console.input));
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+pub fn help_command(In(_): In<Vec<String>>, mut console: ResMut<Console>, registry: Res<ConsoleCommands>) {
+    let lines: Vec<String> = registry.iter().map(|(name, command)| format!("{} {}", name, command.help)).collect();
+    for line in lines {
+        console.print(line);
+    }
+}
+
+pub fn clear_command(In(_): In<Vec<String>>, mut console: ResMut<Console>) {
+    console.clear();
+}
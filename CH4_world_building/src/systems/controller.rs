@@ -0,0 +1,243 @@
+use bevy::prelude::*;
+use avian3d::prelude::*;
+use serde::Deserialize;
+use crate::components::Protagonist;
+use crate::level::read_ron_asset;
+use crate::systems::protagonist::Grounded;
+use crate::systems::stance::{Stance, FEET_OFFSET, PRONE_LENGTH};
+
+use std::f32::consts::FRAC_PI_2;
+
+pub const CONTROLLER_CONFIG_PATH: &str = "config/controller.ron";
+
+// Stands in for the body's mass when turning impulses (jumps) into velocity
+const CONTROLLER_MASS: f32 = 1.0;
+const CAPSULE_RADIUS: f32 = 0.4;
+// Moves shorter than this are dropped instead of cast
+const MIN_MOVE: f32 = 0.0001;
+
+// Which kind of body drives the protagonist
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControllerMode {
+    // The original forced-upright dynamic box, physics does the collisions
+    #[default]
+    Dynamic,
+    // A kinematic capsule that collides and slides on its own
+    Kinematic,
+}
+
+impl ControllerMode {
+    pub fn rigid_body(self) -> RigidBody {
+        match self {
+            ControllerMode::Dynamic => RigidBody::Dynamic,
+            ControllerMode::Kinematic => RigidBody::Kinematic,
+        }
+    }
+
+    /// Body collider for a stance, the capsule has its bottom at the same place as the box
+    pub fn collider(self, stance: Stance) -> Collider {
+        match self {
+            ControllerMode::Dynamic => stance.collider(),
+            ControllerMode::Kinematic => {
+                let height = stance.height();
+                let bottom = -FEET_OFFSET;
+                if stance == Stance::Prone {
+                    // Lying down, so the capsule runs front to back
+                    let radius = height / 2.0;
+                    Collider::compound(vec![(
+                        Vec3::Y * (bottom + radius),
+                        Quat::from_rotation_x(FRAC_PI_2),
+                        Collider::capsule(radius, (PRONE_LENGTH - height).max(0.0)),
+                    )])
+                } else {
+                    Collider::compound(vec![(
+                        Vec3::Y * (bottom + height / 2.0),
+                        Quat::IDENTITY,
+                        Collider::capsule(CAPSULE_RADIUS, height - CAPSULE_RADIUS * 2.0),
+                    )])
+                }
+            }
+        }
+    }
+}
+
+/// Settings for the protagonist's body, read from `assets/config/controller.ron`
+#[derive(Resource, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ControllerConfig {
+    pub mode: ControllerMode,
+    // Force (newtons) the kinematic capsule pushes dynamic bodies like the containers with
+    pub push_force: f32,
+    // Gap kept between the capsule and whatever it slides along
+    pub skin_width: f32,
+    // How many times a move can be deflected along surfaces in one frame
+    pub max_slides: usize,
+}
+
+impl Default for ControllerConfig {
+    fn default() -> Self {
+        Self {
+            mode: ControllerMode::Dynamic,
+            push_force: 4000.0,
+            skin_width: 0.02,
+            max_slides: 4,
+        }
+    }
+}
+
+impl ControllerConfig {
+    /// Falls back to the dynamic body if the file is missing or broken
+    pub fn load(path: &str) -> Self {
+        read_ron_asset(path).unwrap_or_default()
+    }
+}
+
+// Present on a kinematic protagonist
+#[derive(Component, Default)]
+pub struct KinematicController {
+    // Platform velocity added last frame, taken back out before moving again
+    // so standing on a platform doesn't build up speed
+    carried: Vec3,
+}
+
+// Moves `motion` as far as it can, sliding along anything in the way.
+// Returns the distance actually covered.
+#[allow(clippy::too_many_arguments)]
+fn collide_and_slide(
+    spatial_query: &SpatialQuery,
+    collider: &Collider,
+    start: Vec3,
+    rotation: Quat,
+    motion: Vec3,
+    filter: &SpatialQueryFilter,
+    config: &ControllerConfig,
+    sensors: &Query<(), With<Sensor>>,
+    mut on_hit: impl FnMut(Entity, Vec3),
+) -> Vec3 {
+    let mut position = start;
+    let mut remaining = motion;
+
+    for _ in 0..config.max_slides {
+        let distance = remaining.length();
+        let Ok(direction) = Dir3::new(remaining) else {
+            break;
+        };
+        if distance < MIN_MOVE {
+            break;
+        }
+
+        // First solid thing in the way, sensors don't block
+        let hit = spatial_query
+            .shape_hits(collider, position, rotation, direction, distance + config.skin_width, 8, true, filter.clone())
+            .into_iter()
+            .find(|hit| !sensors.contains(hit.entity));
+
+        let Some(hit) = hit else {
+            position += remaining;
+            break;
+        };
+
+        // Up to the surface, leaving the skin gap
+        let travel = (hit.time_of_impact - config.skin_width).max(0.0);
+        position += *direction * travel;
+        on_hit(hit.entity, *direction);
+
+        // Whatever's left slides along the surface
+        let normal = hit.normal1.normalize_or_zero();
+        remaining = *direction * (distance - travel);
+        remaining -= normal * remaining.dot(normal);
+    }
+
+    position - start
+}
+
+/// Moves the kinematic protagonist. Everything else keeps writing `LinearVelocity`
+/// like it does for the dynamic body, this adds gravity and jump impulses, sweeps
+/// the capsule through the world and hands back a velocity that can't go through walls.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn kinematic_controller(
+    time: Res<Time>,
+    gravity: Res<Gravity>,
+    config: Res<ControllerConfig>,
+    spatial_query: SpatialQuery,
+    mut protagonist_query: Query<
+        (
+            Entity,
+            &Transform,
+            &Collider,
+            &mut LinearVelocity,
+            &mut ExternalImpulse,
+            &mut KinematicController,
+            Option<&Grounded>,
+            Option<&GravityScale>,
+        ),
+        With<Protagonist>,
+    >,
+    mut bodies: Query<(&RigidBody, &mut LinearVelocity, Option<&AngularVelocity>, &Transform, Option<&Mass>), Without<Protagonist>>,
+    collider_parents: Query<&ColliderParent>,
+    sensors: Query<(), With<Sensor>>,
+) {
+    let Ok((entity, transform, collider, mut velocity, mut impulse, mut controller, grounded, gravity_scale)) =
+        protagonist_query.get_single_mut()
+    else {
+        return;
+    };
+    let dt = time.delta_seconds();
+    if dt <= 0.0 {
+        return;
+    }
+
+    // Our own velocity, without the platform we were riding last frame
+    let mut own = velocity.0 - controller.carried;
+
+    // Jumps still come in as impulses
+    own += impulse.impulse() / CONTROLLER_MASS;
+    impulse.clear();
+
+    let scale = gravity_scale.map_or(1.0, |scale| scale.0);
+    let on_walkable_ground = grounded.is_some_and(|grounded| grounded.is_walkable());
+    if !on_walkable_ground || own.y > 0.0 {
+        own += gravity.0 * scale * dt;
+    }
+
+    // Ride whatever we're standing on
+    let body_of = |entity: Entity| collider_parents.get(entity).map_or(entity, |parent| parent.get());
+    let carried = grounded
+        .and_then(|grounded| bodies.get(body_of(grounded.entity)).ok())
+        .filter(|(rigid_body, ..)| !rigid_body.is_static())
+        .map_or(Vec3::ZERO, |(_, platform_velocity, angular_velocity, platform_transform, _)| {
+            let spin = angular_velocity.map_or(Vec3::ZERO, |angular| {
+                angular.0.cross(transform.translation - platform_transform.translation)
+            });
+            platform_velocity.0 + spin
+        });
+
+    let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+    let mut pushes = Vec::new();
+    let moved = collide_and_slide(
+        &spatial_query,
+        collider,
+        transform.translation,
+        transform.rotation,
+        (own + carried) * dt,
+        &filter,
+        &config,
+        &sensors,
+        |hit, direction| pushes.push((body_of(hit), direction)),
+    );
+
+    // Shove dynamic bodies we walked into
+    for (body, direction) in pushes {
+        if let Ok((rigid_body, mut body_velocity, _, _, mass)) = bodies.get_mut(body) {
+            if rigid_body.is_dynamic() {
+                let mass = mass.map_or(1.0, |mass| mass.0).max(1.0);
+                body_velocity.0 += direction.with_y(0.0) * config.push_force / mass * dt;
+            }
+        }
+    }
+
+    // Physics moves kinematic bodies by their velocity, so hand back the safe one.
+    // Whatever wasn't blocked carries over, so falls keep accelerating.
+    velocity.0 = moved / dt;
+    controller.carried = carried;
+}
@@ -0,0 +1,216 @@
+// Gizmo overlay for laying out the level. F3 switches it on and off, F4 to F9 switch
+// the categories. There are no guards or navmesh in CH4 yet, so nothing to draw for
+// vision cones or navmesh polygons.
+
+use bevy::prelude::*;
+use avian3d::prelude::*;
+use crate::components::Protagonist;
+use crate::systems::camera::CAMERA_FOLLOW_OFFSET;
+use crate::systems::environment::WaterVolume;
+use crate::systems::portal::{BottomPortalSensor, TopPortalSensor, BOTTOM_PORTAL_JUMP, TOP_PORTAL_JUMP};
+use crate::systems::protagonist::{Grounded, FOOTPRINT_SIZE, GROUND_CHECK_DISTANCE, NORMAL_RAY_HEIGHT};
+use crate::systems::swim::Swimming;
+
+use std::collections::HashSet;
+
+const COLLIDER_COLOR: Color = Color::srgb(1.0, 0.6, 0.1);
+const SENSOR_COLOR: Color = Color::srgb(0.9, 0.2, 0.9);
+const WATER_COLOR: Color = Color::srgb(0.1, 0.5, 1.0);
+const NO_GRAVITY_COLOR: Color = Color::srgb(0.4, 0.9, 1.0);
+const PORTAL_COLOR: Color = Color::srgb(0.6, 0.3, 1.0);
+const CAMERA_ARM_COLOR: Color = Color::srgb(1.0, 1.0, 0.3);
+const CAST_COLOR: Color = Color::srgb(0.7, 0.7, 0.7);
+const GROUNDED_COLOR: Color = Color::srgb(0.2, 1.0, 0.3);
+const AIRBORNE_COLOR: Color = Color::srgb(1.0, 0.2, 0.2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DebugCategory {
+    Colliders,
+    Sensors,
+    // The water box, which is also where gravity is off while swimming
+    Zones,
+    PortalLinks,
+    CameraArm,
+    GroundCasts,
+}
+
+impl DebugCategory {
+    pub const ALL: [DebugCategory; 6] = [
+        DebugCategory::Colliders,
+        DebugCategory::Sensors,
+        DebugCategory::Zones,
+        DebugCategory::PortalLinks,
+        DebugCategory::CameraArm,
+        DebugCategory::GroundCasts,
+    ];
+
+    fn key(self) -> KeyCode {
+        match self {
+            DebugCategory::Colliders => KeyCode::F4,
+            DebugCategory::Sensors => KeyCode::F5,
+            DebugCategory::Zones => KeyCode::F6,
+            DebugCategory::PortalLinks => KeyCode::F7,
+            DebugCategory::CameraArm => KeyCode::F8,
+            DebugCategory::GroundCasts => KeyCode::F9,
+        }
+    }
+}
+
+/// What the overlay draws. Starts off, with every category selected.
+#[derive(Resource, Debug)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+    pub categories: HashSet<DebugCategory>,
+}
+
+impl Default for DebugOverlay {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            categories: DebugCategory::ALL.into_iter().collect(),
+        }
+    }
+}
+
+impl DebugOverlay {
+    pub fn shows(&self, category: DebugCategory) -> bool {
+        self.enabled && self.categories.contains(&category)
+    }
+
+    pub fn toggle(&mut self, category: DebugCategory) {
+        if !self.categories.remove(&category) {
+            self.categories.insert(category);
+        }
+    }
+}
+
+// Run condition for the drawing systems
+pub fn overlay_shows(category: DebugCategory) -> impl Fn(Res<DebugOverlay>) -> bool {
+    move |overlay: Res<DebugOverlay>| overlay.shows(category)
+}
+
+pub fn toggle_debug_overlay(keyboard_input: Res<ButtonInput<KeyCode>>, mut overlay: ResMut<DebugOverlay>) {
+    if keyboard_input.just_pressed(KeyCode::F3) {
+        overlay.enabled = !overlay.enabled;
+        info!("Debug overlay {}", if overlay.enabled { "on" } else { "off" });
+    }
+
+    for category in DebugCategory::ALL {
+        if keyboard_input.just_pressed(category.key()) {
+            overlay.toggle(category);
+            info!("Debug overlay {:?}: {}", category, overlay.categories.contains(&category));
+        }
+    }
+}
+
+/// Colliders and sensors are drawn by avian's debug renderer, this picks per collider
+/// whether and in which colour
+pub fn sync_collider_debug(
+    mut commands: Commands,
+    overlay: Res<DebugOverlay>,
+    mut config_store: ResMut<GizmoConfigStore>,
+    colliders: Query<(Entity, Has<Sensor>, Ref<Collider>)>,
+) {
+    if overlay.is_changed() {
+        config_store.config_mut::<PhysicsGizmos>().0.enabled = overlay.enabled;
+    }
+
+    for (entity, is_sensor, collider) in &colliders {
+        if !overlay.is_changed() && !collider.is_added() {
+            continue;
+        }
+        let (category, color) = if is_sensor {
+            (DebugCategory::Sensors, SENSOR_COLOR)
+        } else {
+            (DebugCategory::Colliders, COLLIDER_COLOR)
+        };
+        let render = if overlay.categories.contains(&category) {
+            DebugRender::none().with_collider_color(color)
+        } else {
+            DebugRender::none()
+        };
+        commands.entity(entity).insert(render);
+    }
+}
+
+// Outline of an axis aligned box
+fn box_outline(gizmos: &mut Gizmos, min: Vec3, max: Vec3, color: Color) {
+    let transform = Transform::from_translation((min + max) / 2.0).with_scale(max - min);
+    gizmos.cuboid(transform, color);
+}
+
+pub fn draw_zones(
+    mut gizmos: Gizmos,
+    water: Res<WaterVolume>,
+    protagonist_query: Query<Has<Swimming>, With<Protagonist>>,
+) {
+    box_outline(&mut gizmos, water.min, water.max, WATER_COLOR);
+
+    // Waterline, brighter while gravity is off
+    let is_swimming = protagonist_query.get_single().unwrap_or(false);
+    let center = (water.min + water.max) / 2.0;
+    let waterline = Transform::from_translation(center.with_y(water.surface_y()))
+        .with_scale((water.max - water.min).with_y(0.0));
+    gizmos.cuboid(waterline, if is_swimming { NO_GRAVITY_COLOR } else { WATER_COLOR });
+}
+
+/// Each portal sensor with an arrow to where it sends you
+pub fn draw_portal_links(
+    mut gizmos: Gizmos,
+    top_sensor_query: Query<&GlobalTransform, With<TopPortalSensor>>,
+    bottom_sensor_query: Query<&GlobalTransform, With<BottomPortalSensor>>,
+) {
+    let sensors = top_sensor_query
+        .iter()
+        .map(|transform| (transform, TOP_PORTAL_JUMP))
+        .chain(bottom_sensor_query.iter().map(|transform| (transform, BOTTOM_PORTAL_JUMP)));
+
+    for (transform, jump) in sensors {
+        let from = transform.translation();
+        gizmos.arrow(from, from + jump, PORTAL_COLOR);
+        gizmos.sphere(from + jump, Quat::IDENTITY, 0.5, PORTAL_COLOR);
+    }
+}
+
+/// Where the camera is, and where it's easing towards
+pub fn draw_camera_arm(
+    mut gizmos: Gizmos,
+    protagonist_query: Query<&Transform, With<Protagonist>>,
+    camera_query: Query<&Transform, (With<Camera3d>, Without<Protagonist>)>,
+) {
+    let Ok(protagonist) = protagonist_query.get_single() else {
+        return;
+    };
+    let target = protagonist.translation + protagonist.rotation * CAMERA_FOLLOW_OFFSET;
+
+    for camera in &camera_query {
+        gizmos.line(protagonist.translation, camera.translation, CAMERA_ARM_COLOR);
+        gizmos.line(camera.translation, target, CAMERA_ARM_COLOR.with_alpha(0.4));
+        gizmos.sphere(target, Quat::IDENTITY, 0.2, CAMERA_ARM_COLOR);
+    }
+}
+
+/// The footprint cast and normal ray from `update_grounded`, green when they hit
+pub fn draw_ground_casts(
+    mut gizmos: Gizmos,
+    protagonist_query: Query<(&Transform, Option<&Grounded>), With<Protagonist>>,
+) {
+    let Ok((transform, grounded)) = protagonist_query.get_single() else {
+        return;
+    };
+    let color = if grounded.is_some() { GROUNDED_COLOR } else { AIRBORNE_COLOR };
+
+    let footprint = Transform::from_translation(transform.translation)
+        .with_rotation(transform.rotation)
+        .with_scale(FOOTPRINT_SIZE);
+    gizmos.cuboid(footprint, CAST_COLOR);
+    gizmos.cuboid(footprint.with_translation(transform.translation - Vec3::Y * GROUND_CHECK_DISTANCE), color);
+
+    let ray_start = transform.translation + Vec3::Y * NORMAL_RAY_HEIGHT;
+    let ray_end = ray_start - Vec3::Y * (NORMAL_RAY_HEIGHT + GROUND_CHECK_DISTANCE * 2.0);
+    gizmos.line(ray_start, ray_end, CAST_COLOR);
+
+    if let Some(grounded) = grounded {
+        gizmos.arrow(transform.translation, transform.translation + grounded.normal, color);
+    }
+}
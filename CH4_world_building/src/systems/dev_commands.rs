@@ -0,0 +1,170 @@
+use bevy::prelude::*;
+use avian3d::prelude::*;
+use crate::components::Protagonist;
+use crate::level::LevelData;
+use crate::resources::{Animations, TimeOfDay, WorldSeed, SCENES};
+use crate::systems::console::{parse_args, Console};
+use crate::systems::controller::ControllerConfig;
+use crate::systems::environment::WorldGravity;
+use crate::systems::health::RespawnPoint;
+use crate::systems::rocks::RockConfig;
+use crate::systems::props::PropRegistry;
+use crate::systems::setup::{
+    reset_protagonist,
+    spawn_container,
+    spawn_random_props,
+    RandomProp,
+};
+
+use std::time::Duration;
+
+// Things `spawn` knows how to make
+pub const SPAWNABLE: [&str; 1] = ["container"];
+
+// How far in front of the protagonist spawned things appear, and how high up
+const SPAWN_DISTANCE: f32 = 8.0;
+const SPAWN_HEIGHT: f32 = 3.0;
+
+/// tp x y z
+pub fn tp_command(
+    In(args): In<Vec<String>>,
+    mut console: ResMut<Console>,
+    mut protagonist_query: Query<(&mut Transform, &mut LinearVelocity), With<Protagonist>>,
+) {
+    let [x, y, z] = match parse_args::<f32, 3>(&args) {
+        Ok(position) => position,
+        Err(error) => return console.print(format!("tp x y z: {}", error)),
+    };
+
+    for (mut transform, mut velocity) in &mut protagonist_query {
+        transform.translation = Vec3::new(x, y, z);
+        velocity.0 = Vec3::ZERO;
+    }
+    console.print(format!("Teleported to {} {} {}", x, y, z));
+}
+
+/// spawn container
+pub fn spawn_command(
+    In(args): In<Vec<String>>,
+    mut console: ResMut<Console>,
+    mut commands: Commands,
+    props: Res<PropRegistry>,
+    protagonist_query: Query<&Transform, With<Protagonist>>,
+) {
+    let Ok(protagonist) = protagonist_query.get_single() else {
+        return console.print("No protagonist to spawn in front of");
+    };
+    let transform = Transform::from_translation(
+        protagonist.translation + protagonist.forward() * SPAWN_DISTANCE + Vec3::Y * SPAWN_HEIGHT,
+    )
+    .with_rotation(protagonist.rotation);
+
+    match args.first().map(String::as_str) {
+        Some("container") => {
+            spawn_container(&mut commands, &props, transform);
+            console.print("Spawned a container");
+        }
+        _ => console.print(format!("spawn {}", SPAWNABLE.join("|"))),
+    }
+}
+
+/// gravity x y z, for everywhere outside the water
+pub fn gravity_command(
+    In(args): In<Vec<String>>,
+    mut console: ResMut<Console>,
+    mut world_gravity: ResMut<WorldGravity>,
+) {
+    match parse_args::<f32, 3>(&args) {
+        Ok([x, y, z]) => {
+            world_gravity.0 = Vec3::new(x, y, z);
+            console.print(format!("Gravity is now {} {} {}", x, y, z));
+        }
+        Err(error) => console.print(format!("gravity x y z: {}", error)),
+    }
+}
+
+/// anim CLIP, by its name in `SCENES`
+pub fn anim_command(
+    In(args): In<Vec<String>>,
+    mut console: ResMut<Console>,
+    animations: Res<Animations>,
+    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+) {
+    let name = args.first().map(|name| name.to_uppercase()).unwrap_or_default();
+    let Some(&index) = SCENES.get(name.as_str()) else {
+        return console.print(format!("anim: no clip called {:?}", name));
+    };
+
+    for (mut player, mut transitions) in &mut animation_players {
+        transitions
+            .play(&mut player, animations.animations[index], Duration::from_millis(250))
+            .repeat();
+    }
+    console.print(format!("Playing {}", name));
+}
+
+/// time HH:MM [hours per second]
+pub fn time_command(
+    In(args): In<Vec<String>>,
+    mut console: ResMut<Console>,
+    mut time_of_day: ResMut<TimeOfDay>,
+) {
+    let usage = "time HH:MM [hours per second]";
+    let Some((hours, minutes)) = args.first().and_then(|time| time.split_once(':')) else {
+        return console.print(usage);
+    };
+    let (Ok(hours), Ok(minutes)) = (hours.parse::<f32>(), minutes.parse::<f32>()) else {
+        return console.print(usage);
+    };
+
+    time_of_day.hour = (hours + minutes / 60.0).rem_euclid(24.0);
+    if let Some(speed) = args.get(1) {
+        match speed.parse() {
+            Ok(speed) => time_of_day.speed = speed,
+            Err(_) => return console.print(usage),
+        }
+    }
+    console.print(format!("Time is {}, {} hours per second", args[0], time_of_day.speed));
+}
+
+/// seed N, places the random props again from the new seed
+#[allow(clippy::too_many_arguments)]
+pub fn seed_command(
+    In(args): In<Vec<String>>,
+    mut console: ResMut<Console>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    props: Res<PropRegistry>,
+    level: Res<LevelData>,
+    rock_config: Res<RockConfig>,
+    mut world_seed: ResMut<WorldSeed>,
+    random_props: Query<Entity, With<RandomProp>>,
+) {
+    let [seed] = match parse_args::<u64, 1>(&args) {
+        Ok(seed) => seed,
+        Err(error) => return console.print(format!("seed N: {}", error)),
+    };
+
+    for entity in &random_props {
+        commands.entity(entity).despawn_recursive();
+    }
+    world_seed.0 = seed;
+    let player_start = level.player_start.transform().translation;
+    spawn_random_props(&mut commands, &asset_server, &mut meshes, &props, &rock_config, seed, player_start);
+    console.print(format!("World seed is now {}", seed));
+}
+
+/// reset, the protagonist comes back fresh at the last checkpoint
+pub fn reset_command(
+    In(_): In<Vec<String>>,
+    mut console: ResMut<Console>,
+    mut commands: Commands,
+    protagonist_query: Query<Entity, With<Protagonist>>,
+    asset_server: Res<AssetServer>,
+    respawn_point: Res<RespawnPoint>,
+    controller_config: Res<ControllerConfig>,
+) {
+    reset_protagonist(&mut commands, &protagonist_query, &asset_server, &controller_config, respawn_point.0);
+    console.print("Reset");
+}
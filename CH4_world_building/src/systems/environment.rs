@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use avian3d::prelude::*;
+use crate::components::Protagonist;
+use crate::systems::swim::Swimming;
+
+const NORMAL_LIGHT_COLOR: Color = Color::srgb(0.2, 0.2, 0.3);
+const NORMAL_LIGHT_ILLUMINANCE: f32 = 10.0;
+const UNDERWATER_LIGHT_COLOR: Color = Color::srgb(0.0, 0.2, 0.8); // Deep blue color
+const UNDERWATER_LIGHT_ILLUMINANCE: f32 = 50000.0; // Dimmer underwater
+
+/// The aquifer: an axis aligned box of water, the top face is the waterline
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct WaterVolume {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Default for WaterVolume {
+    fn default() -> Self {
+        // Inside the concrete walls, from the aquifer floor up to the underside of the invisible floor
+        Self {
+            min: Vec3::new(-40.0, -80.0, -50.0),
+            max: Vec3::new(40.0, -5.3, 50.0),
+        }
+    }
+}
+
+impl WaterVolume {
+    pub fn contains(&self, position: Vec3) -> bool {
+        position.cmpge(self.min).all() && position.cmple(self.max).all()
+    }
+
+    pub fn surface_y(&self) -> f32 {
+        self.max.y
+    }
+}
+
+// Gravity everywhere outside the water, the physics `Gravity` follows this
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct WorldGravity(pub Vec3);
+
+impl Default for WorldGravity {
+    fn default() -> Self {
+        Self(Vec3::new(0.0, -9.81, 0.0))
+    }
+}
+
+/// Gravity is off while swimming and back on everywhere else, including walking the aquifer floor
+pub fn update_gravity(
+    world_gravity: Res<WorldGravity>,
+    mut gravity: ResMut<Gravity>,
+    protagonist_query: Query<Has<Swimming>, With<Protagonist>>,
+) {
+    if let Ok(is_swimming) = protagonist_query.get_single() {
+        let wanted = if is_swimming {
+            Vec3::ZERO
+        } else {
+            world_gravity.0
+        };
+
+        // Avoid touching the resource every frame
+        if gravity.0 != wanted {
+            gravity.0 = wanted;
+        }
+    }
+}
+
+pub fn update_underwater_lighting(
+    water: Res<WaterVolume>,
+    protagonist_query: Query<&Transform, With<Protagonist>>,
+    mut directional_light_query: Query<&mut DirectionalLight>,
+) {
+    if let Ok(protagonist_transform) = protagonist_query.get_single() {
+        let is_underwater = water.contains(protagonist_transform.translation);
+
+        for mut light in directional_light_query.iter_mut() {
+            if is_underwater {
+                light.illuminance = UNDERWATER_LIGHT_ILLUMINANCE;
+                light.color = UNDERWATER_LIGHT_COLOR;
+            } else {
+                light.illuminance = NORMAL_LIGHT_ILLUMINANCE;
+                light.color = NORMAL_LIGHT_COLOR;
+            }
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use crate::components::Protagonist;
+use crate::resources::{Animations, SCENES};
+use crate::systems::input::FallingState;
+use crate::systems::protagonist::Grounded;
+use crate::systems::climb::ClimbState;
+use crate::systems::swim::Swimming;
+
+use avian3d::prelude::*;
+use bevy::{
+    animation::RepeatAnimation,
+    prelude::*,
+};
+
+use std::time::Duration;
+
+// Landing slower than this is free, anything faster hurts
+const SAFE_FALL_SPEED: f32 = 12.0;
+// Damage per unit of impact speed above the safe speed
+const FALL_DAMAGE_PER_SPEED: f32 = 5.0;
+// How long the protagonist stays dead before respawning
+const RESPAWN_DELAY_SECS: f32 = 3.0;
+
+#[derive(Component)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+}
+
+// Present while the protagonist is dead, input is ignored until it is removed
+#[derive(Component)]
+pub struct Dead {
+    respawn_timer: Timer,
+}
+
+// Colliders with this component hurt anything with `Health` touching them
+#[derive(Component)]
+pub struct Hazard {
+    pub damage_per_second: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageSource {
+    Fall,
+    Explosion,
+    Hazard,
+}
+
+#[derive(Event)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: f32,
+    pub source: DamageSource,
+}
+
+// Damage falls off linearly from `damage` at the center to zero at `radius`
+#[derive(Event)]
+pub struct Explosion {
+    pub position: Vec3,
+    pub radius: f32,
+    pub damage: f32,
+}
+
+// Where the protagonist comes back after dying
+#[derive(Resource)]
+pub struct RespawnPoint(pub Transform);
+
+impl Default for RespawnPoint {
+    fn default() -> Self {
+        Self(Transform::from_xyz(0.0, 1.0, 0.0))
+    }
+}
+
+/// Tracks the fastest downward speed while airborne and turns it into damage on landing
+#[allow(clippy::type_complexity)]
+pub fn apply_fall_damage(
+    mut protagonist_query: Query<
+        (Entity, &LinearVelocity, &mut FallingState, Has<Grounded>, Has<Swimming>),
+        (With<Protagonist>, Without<Dead>),
+    >,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (entity, velocity, mut falling_state, is_grounded, is_swimming) in &mut protagonist_query {
+        // The water breaks any fall
+        if is_swimming {
+            falling_state.peak_fall_speed = 0.0;
+            continue;
+        }
+
+        if !is_grounded {
+            falling_state.peak_fall_speed = falling_state.peak_fall_speed.max(-velocity.0.y);
+            continue;
+        }
+
+        let impact_speed = falling_state.peak_fall_speed;
+        falling_state.peak_fall_speed = 0.0;
+
+        if impact_speed > SAFE_FALL_SPEED {
+            let amount = (impact_speed - SAFE_FALL_SPEED) * FALL_DAMAGE_PER_SPEED;
+            dev_log!("Landed at {:.1} m/s, taking {:.1} fall damage", impact_speed, amount);
+            damage_events.send(DamageEvent {
+                target: entity,
+                amount,
+                source: DamageSource::Fall,
+            });
+        }
+    }
+}
+
+pub fn apply_explosion_damage(
+    mut explosions: EventReader<Explosion>,
+    targets: Query<(Entity, &Transform), With<Health>>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for explosion in explosions.read() {
+        for (entity, transform) in &targets {
+            let distance = transform.translation.distance(explosion.position);
+            if distance < explosion.radius {
+                damage_events.send(DamageEvent {
+                    target: entity,
+                    amount: explosion.damage * (1.0 - distance / explosion.radius),
+                    source: DamageSource::Explosion,
+                });
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn apply_hazard_damage(
+    time: Res<Time>,
+    targets: Query<(Entity, &CollidingEntities), (With<Health>, Without<Dead>)>,
+    hazards: Query<&Hazard>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (entity, colliding_entities) in &targets {
+        for hazard in hazards.iter_many(colliding_entities.iter()) {
+            damage_events.send(DamageEvent {
+                target: entity,
+                amount: hazard.damage_per_second * time.delta_seconds(),
+                source: DamageSource::Hazard,
+            });
+        }
+    }
+}
+
+/// Applies queued damage and kills anything that drops to zero health
+pub fn handle_damage(
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageEvent>,
+    mut health_query: Query<&mut Health, Without<Dead>>,
+    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+    animations: Res<Animations>,
+) {
+    for event in damage_events.read() {
+        let Ok(mut health) = health_query.get_mut(event.target) else {
+            continue;
+        };
+
+        // Several events can land in the same frame, only the first one kills
+        if health.current <= 0.0 {
+            continue;
+        }
+
+        health.current = (health.current - event.amount).max(0.0);
+        dev_log!("{:?} damage {:.1}, health now {:.1}/{:.1}", event.source, event.amount, health.current, health.max);
+
+        if health.current <= 0.0 {
+            info!("Protagonist died");
+            commands
+                .entity(event.target)
+                .insert(Dead {
+                    respawn_timer: Timer::from_seconds(RESPAWN_DELAY_SECS, TimerMode::Once),
+                })
+                // Let go of any ledge, and stop swimming
+                .remove::<(ClimbState, GravityScale, Swimming)>();
+
+            // The protagonist's glTF has no death clip, so unless one gets added to
+            // SCENES the body just holds whatever pose it died in
+            let Some(&death) = SCENES.get("DEATH") else {
+                warn_once!("SCENES has no DEATH clip, the protagonist freezes on dying instead");
+                for (mut player, _) in &mut animation_players {
+                    player.pause_all();
+                }
+                continue;
+            };
+            for (mut player, mut transitions) in &mut animation_players {
+                transitions
+                    .play(&mut player, animations.animations[death], Duration::from_millis(250))
+                    .set_repeat(RepeatAnimation::Never);
+            }
+        }
+    }
+}
+
+/// Brings the protagonist back at the respawn point once the death timer runs out
+#[allow(clippy::type_complexity)]
+pub fn respawn_dead(
+    mut commands: Commands,
+    time: Res<Time>,
+    respawn_point: Res<RespawnPoint>,
+    mut dead_query: Query<
+        (Entity, &mut Dead, &mut Health, &mut Transform, &mut LinearVelocity, &mut FallingState),
+        With<Protagonist>,
+    >,
+    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+    animations: Res<Animations>,
+) {
+    for (entity, mut dead, mut health, mut transform, mut velocity, mut falling_state) in &mut dead_query {
+        // Keep the body still while dead
+        velocity.0 = Vec3::ZERO;
+
+        dead.respawn_timer.tick(time.delta());
+        if !dead.respawn_timer.finished() {
+            continue;
+        }
+
+        *transform = respawn_point.0;
+        health.current = health.max;
+        falling_state.peak_fall_speed = 0.0;
+        commands.entity(entity).remove::<Dead>();
+        info!("Respawned at {:?}", transform.translation);
+
+        let stretch = *SCENES.get("IDLE_STRETCH").unwrap();
+        for (mut player, mut transitions) in &mut animation_players {
+            player.resume_all();
+            transitions
+                .play(
+                    &mut player,
+                    animations.animations[stretch],
+                    Duration::from_millis(250),
+                )
+                .repeat();
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+use crate::systems::objectives::Objectives;
+use crate::systems::interaction::{Interactable, InteractionTarget};
+
+// Marker for the objective list in the top left corner
+#[derive(Component)]
+pub struct ObjectiveText;
+
+// Marker for the "[F] ..." prompt near the bottom of the screen
+#[derive(Component)]
+pub struct InteractionPromptText;
+
+pub fn setup_hud(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            left: Val::Px(12.0),
+            ..default()
+        }),
+        ObjectiveText,
+    ));
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 24.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(48.0),
+            left: Val::Percent(45.0),
+            ..default()
+        }),
+        InteractionPromptText,
+    ));
+}
+
+/// Lists the active objectives, only rebuilt when the objectives change
+pub fn update_objective_hud(
+    objectives: Res<Objectives>,
+    mut text_query: Query<&mut Text, With<ObjectiveText>>,
+) {
+    if !objectives.is_changed() {
+        return;
+    }
+
+    let total = objectives.iter().count();
+    if total == 0 {
+        return;
+    }
+
+    let mut lines = vec![format!("Objectives {}/{}", objectives.completed().count(), total)];
+    lines.extend(objectives.active().map(|objective| format!("- {}", objective.title)));
+
+    for mut text in &mut text_query {
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+pub fn update_interaction_prompt(
+    target: Res<InteractionTarget>,
+    interactables: Query<&Interactable>,
+    mut text_query: Query<&mut Text, With<InteractionPromptText>>,
+) {
+    if !target.is_changed() {
+        return;
+    }
+
+    let prompt = target
+        .0
+        .and_then(|entity| interactables.get(entity).ok())
+        .map(|interactable| format!("[F] {}", interactable.prompt))
+        .unwrap_or_default();
+
+    for mut text in &mut text_query {
+        text.sections[0].value = prompt.clone();
+    }
+}
@@ -1,5 +1,15 @@
 use crate::components::Protagonist;
 use crate::resources::{Animations, SCENES};
+use crate::systems::health::{Dead, Explosion};
+use crate::systems::protagonist::Grounded;
+use crate::events::GameEvent;
+use crate::systems::actions::{Action, ActionState};
+use crate::systems::interaction::{Interactable, Interacted};
+use crate::systems::climb::ClimbState;
+use crate::systems::stance::{Stance, play_rest_pose};
+use crate::systems::swim::Swimming;
+use crate::systems::markers::{AnimationMarker, AnimationMarkerReached};
+use crate::systems::audio::{PlaySound, SoundCue};
 
 
 use bevy::{
@@ -9,6 +19,7 @@ use bevy::{
 };
 
 use avian3d::prelude::*;
+use std::f32::consts::FRAC_PI_3;
 use std::time::Duration;
 use bevy::pbr::StandardMaterial;  // Add these imports
 
@@ -22,6 +33,17 @@ const CHARGE_LIGHT_RADIUS: f32 = 0.2;  // Add physical size to the light
 const CHARGE_MESH_RADIUS: f32 = 0.1;  // Hockey puck radius (adjust as needed)
 const CHARGE_MESH_HEIGHT: f32 = 0.05;  // Hockey puck height
 
+// Longest we wait for the crouch's hand plant before putting the charge down anyway
+const CHARGE_PLACE_FALLBACK_SECS: f32 = 1.5;
+
+// Charges blow up with G
+const CHARGE_EXPLOSION_RADIUS: f32 = 8.0;
+const CHARGE_EXPLOSION_DAMAGE: f32 = 80.0;
+
+// Picking a charge back up needs it within reach and roughly in front
+const CHARGE_PICKUP_RANGE: f32 = 2.0;
+const CHARGE_PICKUP_ANGLE: f32 = FRAC_PI_3;
+
 // Add these constants near the other light constants
 const BACKPACK_LIGHT_COLOR: Color = Color::srgb(1.0, 0.5, 0.0);  // Orange color
 const BACKPACK_LIGHT_INTENSITY: f32 = 100000.0;  // Increased from 1000.0
@@ -35,123 +57,81 @@ pub struct TemporaryLight {
 }
 
 // Add this near the top with other components
-#[derive(Component)]
+#[derive(Component, Default)]
 pub struct FallingState {
-    was_falling: bool,
+    // Fastest downward speed since leaving the ground, used for fall damage
+    pub peak_fall_speed: f32,
 }
 
+// Marker for placed charges so they can be detonated later
+#[derive(Component)]
+pub struct Charge;
+
+// Present between pressing C and the charge going down
+#[derive(Component)]
+pub struct PlacingCharge {
+    // In case the crouch gets interrupted before the hand plant marker
+    fallback: Timer,
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn keyboard_animation_control(
     mut commands: Commands,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     time: Res<Time>,
     mut impulse_query: Query<&mut ExternalImpulse, With<Protagonist>>,
-    mut protagonist_query: Query<(&mut Transform, Has<Collider>), With<Protagonist>>,
+    mut protagonist_query: Query<(Entity, &mut Transform, Has<Grounded>, &Stance), (With<Protagonist>, Without<Dead>, Without<ClimbState>, Without<Swimming>)>,
     mut velocity_query: Query<&mut LinearVelocity, With<Protagonist>>,
     mut angular_velocity_query: Query<&mut AngularVelocity, With<Protagonist>>,
     mut directional_light_query: Query<&mut DirectionalLight>,
     mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
     animations: Res<Animations>,
-    mut current_animation: Local<usize>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    mut falling_query: Query<&mut FallingState, With<Protagonist>>,
+    mut sounds: EventWriter<PlaySound>,
 ) {
     let turn_speed = 2.0 * time.delta_seconds(); // Rotation speed (radians per second)
     let move_speed = 5.0; // Units per second
     let run_speed = 10.0; // Running speed
     let strafe_speed = 4.0; // Strafing speed
-    let underwater_speed = 8.0; // Underwater movement speed
-
-    // Add wall boundaries
-    const WALL_MIN_X: f32 = -25.0;  // Left wall
-    const WALL_MAX_X: f32 = 25.0;   // Right wall
-    const WALL_MIN_Z: f32 = -25.0;  // Front wall
-    const WALL_MAX_Z: f32 = 25.0;   // Back wall
-    const WATER_LEVEL: f32 = -1.0;  // Water level threshold
-
-    const NORMAL_LIGHT_COLOR: Color = Color::srgb(0.2, 0.2, 0.3);
-    const NORMAL_LIGHT_ILLUMINANCE: f32 = 10.0;
-    const UNDERWATER_LIGHT_COLOR: Color = Color::srgb(0.0, 0.2, 0.8); // Deep blue color
-    const UNDERWATER_LIGHT_ILLUMINANCE: f32 = 50000.0; // Dimmer underwater
 
-    if let Ok((mut protagonist_transform, is_colliding)) = protagonist_query.get_single_mut() {
+    if let Ok((protagonist, mut protagonist_transform, is_grounded, stance)) = protagonist_query.get_single_mut() {
+        let stance = *stance;
         // Extract only Y rotation and force upright orientation
         let (yaw, _, _) = protagonist_transform.rotation.to_euler(EulerRot::YXZ);
         protagonist_transform.rotation = Quat::from_rotation_y(yaw);
         
-        // Check if character is both below water level AND within walls
-        let is_underwater = protagonist_transform.translation.y < WATER_LEVEL
-            && protagonist_transform.translation.x > WALL_MIN_X
-            && protagonist_transform.translation.x < WALL_MAX_X
-            && protagonist_transform.translation.z > WALL_MIN_Z
-            && protagonist_transform.translation.z < WALL_MAX_Z;
-
-        // info!("Position: {:?}, Is Underwater: {}", protagonist_transform.translation, is_underwater);
-        
         for (mut player, mut transitions) in &mut animation_players {
-            if is_underwater {
-                // Play SWIM animation when moving forward/backward or TREAD when moving up/down
-                if keyboard_input.pressed(KeyCode::KeyW) || keyboard_input.pressed(KeyCode::KeyS) {
-                    let swim = *SCENES.get("SWIM").unwrap();
-                    if player.animation(animations.animations[swim]).is_none() {
-                        transitions
-                            .play(
-                                &mut player,
-                                animations.animations[swim],
-                                Duration::from_millis(250),
-                            )
-                            .set_repeat(RepeatAnimation::Forever);
-                        player.resume_all();
-                    }
-                } else {
-                    // Default to TREAD animation when not swimming forward/backward
-                    let tread = *SCENES.get("TREAD").unwrap();
-                    if player.animation(animations.animations[tread]).is_none() {
+            // Check if falling
+            if let Ok(velocity) = velocity_query.get_single() {
+                let is_falling = velocity.0.y < -0.1 && !is_grounded;  // Only consider falling when not touching ground
+                
+                // Play fly animation when falling
+                if is_falling {
+                    let fly = *SCENES.get("FLY").unwrap();
+                    if !player.is_playing_animation(animations.animations[fly]) {
                         transitions
                             .play(
                                 &mut player,
-                                animations.animations[tread],
+                                animations.animations[fly],
                                 Duration::from_millis(250),
                             )
                             .set_repeat(RepeatAnimation::Forever);
-                        player.resume_all();
                     }
-                }
-            } else {
-                // Check if falling
-                if let (Ok(velocity), Ok(mut falling_state)) = (velocity_query.get_single(), falling_query.get_single_mut()) {
-                    let is_falling = velocity.0.y < -0.1 && !is_colliding;  // Only consider falling when not touching ground
                     
-                    // Play fly animation when falling
-                    if is_falling {
-                        let fly = *SCENES.get("FLY").unwrap();
-                        if !player.is_playing_animation(animations.animations[fly]) {
-                            transitions
-                                .play(
-                                    &mut player,
-                                    animations.animations[fly],
-                                    Duration::from_millis(250),
-                                )
-                                .set_repeat(RepeatAnimation::Forever);
-                        }
-                        
-                        // Prevent movement controls while in air
-                        for mut linear_velocity in velocity_query.iter_mut() {
-                            linear_velocity.0.x = 0.0;
-                            linear_velocity.0.z = 0.0;
-                        }
+                    // Prevent movement controls while in air
+                    for mut linear_velocity in velocity_query.iter_mut() {
+                        linear_velocity.0.x = 0.0;
+                        linear_velocity.0.z = 0.0;
                     }
-                    
-                    falling_state.was_falling = is_falling;
                 }
             }
 
             // Handle turning left (A)
-            if keyboard_input.pressed(KeyCode::KeyA) {
+            if actions.pressed(Action::TurnLeft) {
                 // Apply pure Y-axis rotation
                 protagonist_transform.rotation = Quat::from_rotation_y(yaw + turn_speed);
 
-                if !is_underwater && !keyboard_input.pressed(KeyCode::KeyW) && !keyboard_input.pressed(KeyCode::KeyS) {
+                if stance == Stance::Standing && !actions.pressed(Action::Forward) && !actions.pressed(Action::Backward) {
                     let pivot_left = *SCENES.get("PIVOT_RIGHT").unwrap();
                     transitions
                         .play(
@@ -163,25 +143,19 @@ pub fn keyboard_animation_control(
                 }
             }
 
-            if keyboard_input.just_released(KeyCode::KeyA) {
-                if !is_underwater && !keyboard_input.pressed(KeyCode::KeyW) && !keyboard_input.pressed(KeyCode::KeyS) {
-                    let crouch = *SCENES.get("CROUCH").unwrap();
-                    transitions
-                        .play(
-                            &mut player,
-                            animations.animations[crouch],
-                            Duration::from_millis(250),
-                        )
-                        .set_repeat(RepeatAnimation::Count(1));
-                }
+            if actions.just_released(Action::TurnLeft)
+                && !actions.pressed(Action::Forward)
+                && !actions.pressed(Action::Backward)
+            {
+                play_rest_pose(&mut player, &mut transitions, &animations, stance);
             }
 
             // Handle turning right (D)
-            if keyboard_input.pressed(KeyCode::KeyD) {
+            if actions.pressed(Action::TurnRight) {
                 // Apply pure Y-axis rotation
                 protagonist_transform.rotation = Quat::from_rotation_y(yaw - turn_speed);
 
-                if !is_underwater && !keyboard_input.pressed(KeyCode::KeyW) && !keyboard_input.pressed(KeyCode::KeyS) {
+                if stance == Stance::Standing && !actions.pressed(Action::Forward) && !actions.pressed(Action::Backward) {
                     let pivot_right = *SCENES.get("PIVOT_RIGHT").unwrap();
                     transitions
                         .play(
@@ -193,55 +167,38 @@ pub fn keyboard_animation_control(
                 }
             }
 
-            if keyboard_input.just_released(KeyCode::KeyD) {
-                if !is_underwater && !keyboard_input.pressed(KeyCode::KeyW) && !keyboard_input.pressed(KeyCode::KeyS) {
-                    let crouch = *SCENES.get("CROUCH").unwrap();
-                    transitions
-                        .play(
-                            &mut player,
-                            animations.animations[crouch],
-                            Duration::from_millis(250),
-                        )
-                        .set_repeat(RepeatAnimation::Count(1));
-                }
+            if actions.just_released(Action::TurnRight)
+                && !actions.pressed(Action::Forward)
+                && !actions.pressed(Action::Backward)
+            {
+                play_rest_pose(&mut player, &mut transitions, &animations, stance);
             }
 
             // Handle strafing left (Q)
-            if keyboard_input.just_pressed(KeyCode::KeyQ) {
-                if !is_underwater {
-                    let strafe_left = *SCENES.get("STRAFE_LEFT").unwrap();
-                    transitions
-                        .play(
-                            &mut player,
-                            animations.animations[strafe_left],
-                            Duration::from_millis(250),
-                        )
-                        .set_repeat(RepeatAnimation::Forever);
-                }
+            if actions.just_pressed(Action::StrafeLeft) {
+                let strafe_left = *SCENES.get("STRAFE_LEFT").unwrap();
+                transitions
+                    .play(
+                        &mut player,
+                        animations.animations[strafe_left],
+                        Duration::from_millis(250),
+                    )
+                    .set_repeat(RepeatAnimation::Forever);
             }
 
-            if keyboard_input.pressed(KeyCode::KeyQ) {
+            if actions.pressed(Action::StrafeLeft) {
                 let right = protagonist_transform.rotation * Vec3::X;
                 let strafe_direction = -Vec3::new(right.x, 0.0, right.z).normalize();
                 
                 for mut linear_velocity in velocity_query.iter_mut() {
                     let current_y = linear_velocity.0.y;
-                    linear_velocity.0 = strafe_direction * strafe_speed;
+                    linear_velocity.0 = strafe_direction * strafe_speed * stance.speed_multiplier();
                     linear_velocity.0.y = current_y;
                 }
             }
 
-            if keyboard_input.just_released(KeyCode::KeyQ) {
-                if !is_underwater {
-                    let crouch = *SCENES.get("CROUCH").unwrap();
-                    transitions
-                        .play(
-                            &mut player,
-                            animations.animations[crouch],
-                            Duration::from_millis(250),
-                        )
-                        .set_repeat(RepeatAnimation::Count(1));
-                }
+            if actions.just_released(Action::StrafeLeft) {
+                play_rest_pose(&mut player, &mut transitions, &animations, stance);
                 
                 for mut linear_velocity in velocity_query.iter_mut() {
                     linear_velocity.0 = Vec3::ZERO;
@@ -249,41 +206,30 @@ pub fn keyboard_animation_control(
             }
 
             // Handle strafing right (E)
-            if keyboard_input.just_pressed(KeyCode::KeyE) {
-                if !is_underwater {
-                    let strafe_right = *SCENES.get("STRAFE_RIGHT").unwrap();
-                    transitions
-                        .play(
-                            &mut player,
-                            animations.animations[strafe_right],
-                            Duration::from_millis(250),
-                        )
-                        .set_repeat(RepeatAnimation::Forever);
-                }
+            if actions.just_pressed(Action::StrafeRight) {
+                let strafe_right = *SCENES.get("STRAFE_RIGHT").unwrap();
+                transitions
+                    .play(
+                        &mut player,
+                        animations.animations[strafe_right],
+                        Duration::from_millis(250),
+                    )
+                    .set_repeat(RepeatAnimation::Forever);
             }
 
-            if keyboard_input.pressed(KeyCode::KeyE) {
+            if actions.pressed(Action::StrafeRight) {
                 let right = protagonist_transform.rotation * Vec3::X;
                 let strafe_direction = Vec3::new(right.x, 0.0, right.z).normalize();
                 
                 for mut linear_velocity in velocity_query.iter_mut() {
                     let current_y = linear_velocity.0.y;
-                    linear_velocity.0 = strafe_direction * strafe_speed;
+                    linear_velocity.0 = strafe_direction * strafe_speed * stance.speed_multiplier();
                     linear_velocity.0.y = current_y;
                 }
             }
 
-            if keyboard_input.just_released(KeyCode::KeyE) {
-                if !is_underwater {
-                    let crouch = *SCENES.get("CROUCH").unwrap();
-                    transitions
-                        .play(
-                            &mut player,
-                            animations.animations[crouch],
-                            Duration::from_millis(250),
-                        )
-                        .set_repeat(RepeatAnimation::Count(1));
-                }
+            if actions.just_released(Action::StrafeRight) {
+                play_rest_pose(&mut player, &mut transitions, &animations, stance);
                 
                 for mut linear_velocity in velocity_query.iter_mut() {
                     linear_velocity.0 = Vec3::ZERO;
@@ -291,71 +237,39 @@ pub fn keyboard_animation_control(
             }
 
             // Handle forward movement (W)
-            if keyboard_input.just_pressed(KeyCode::KeyW) {
-                if !is_underwater {
-                    let advance = *SCENES.get("LEFT_SHOULDER_ADVANCE").unwrap();
-                    transitions
-                        .play(
-                            &mut player,
-                            animations.animations[advance],
-                            Duration::from_millis(250),
-                        )
-                        .set_repeat(RepeatAnimation::Forever);
-                } else {
-                    let swim = *SCENES.get("SWIM").unwrap();
-                    transitions
-                        .play(
-                            &mut player,
-                            animations.animations[swim],
-                            Duration::from_millis(250),
-                        )
-                        .set_repeat(RepeatAnimation::Forever);
-                }
+            if actions.just_pressed(Action::Forward) {
+                let advance = *SCENES.get(stance.forward_clip()).unwrap();
+                transitions
+                    .play(
+                        &mut player,
+                        animations.animations[advance],
+                        Duration::from_millis(250),
+                    )
+                    .set_repeat(RepeatAnimation::Forever);
             }
 
-            if keyboard_input.pressed(KeyCode::KeyW) {
-                if !is_underwater {
-                    // Calculate forward vector based on rotation, but remove vertical component
-                    let forward = protagonist_transform.rotation * Vec3::Z;
-                    let forward_flat = Vec3::new(forward.x, 0.0, forward.z).normalize();
-                    let forward_direction = -forward_flat;
-                    let current_speed = if keyboard_input.pressed(KeyCode::ShiftLeft) {
-                        run_speed
-                    } else {
-                        move_speed
-                    };
-
-                    for mut linear_velocity in velocity_query.iter_mut() {
-                        // Preserve existing vertical velocity (for gravity)
-                        let current_y = linear_velocity.0.y;
-                        linear_velocity.0 = forward_direction * current_speed;
-                        linear_velocity.0.y = current_y;
-                    }
+            if actions.pressed(Action::Forward) {
+                // Calculate forward vector based on rotation, but remove vertical component
+                let forward = protagonist_transform.rotation * Vec3::Z;
+                let forward_flat = Vec3::new(forward.x, 0.0, forward.z).normalize();
+                let forward_direction = -forward_flat;
+                // Can only run upright
+                let current_speed = if actions.pressed(Action::Run) && stance == Stance::Standing {
+                    run_speed
                 } else {
-                    // Underwater vertical movement
-                    for mut linear_velocity in velocity_query.iter_mut() {
-                        if keyboard_input.pressed(KeyCode::ShiftLeft) {
-                            let forward = protagonist_transform.rotation * Vec3::Z;
-                            let forward_direction = -forward.normalize();
-                            linear_velocity.0 = forward_direction * underwater_speed;
-                        } else {
-                            linear_velocity.0 = Vec3::new(0.0, underwater_speed, 0.0);
-                        }
-                    }
+                    move_speed * stance.speed_multiplier()
+                };
+
+                for mut linear_velocity in velocity_query.iter_mut() {
+                    // Preserve existing vertical velocity (for gravity)
+                    let current_y = linear_velocity.0.y;
+                    linear_velocity.0 = forward_direction * current_speed;
+                    linear_velocity.0.y = current_y;
                 }
             }
 
-            if keyboard_input.just_released(KeyCode::KeyW) {
-                if !is_underwater {
-                    let crouch = *SCENES.get("CROUCH").unwrap();
-                    transitions
-                        .play(
-                            &mut player,
-                            animations.animations[crouch],
-                            Duration::from_millis(250),
-                        )
-                        .set_repeat(RepeatAnimation::Count(1));
-                }
+            if actions.just_released(Action::Forward) {
+                play_rest_pose(&mut player, &mut transitions, &animations, stance);
 
                 for mut linear_velocity in velocity_query.iter_mut() {
                     linear_velocity.0 = Vec3::ZERO;
@@ -363,58 +277,40 @@ pub fn keyboard_animation_control(
             }
 
             // Handle backward movement (S)
-            if keyboard_input.just_pressed(KeyCode::KeyS) {
-                if !is_underwater {
-                    let walk_backwards = *SCENES.get("JOG_BACK").unwrap();
-                    transitions
-                        .play(
-                            &mut player,
-                            animations.animations[walk_backwards],
-                            Duration::from_millis(250),
-                        )
-                        .set_repeat(RepeatAnimation::Forever);
-                }
+            if actions.just_pressed(Action::Backward) {
+                let walk_backwards = *SCENES.get(stance.backward_clip()).unwrap();
+                transitions
+                    .play(
+                        &mut player,
+                        animations.animations[walk_backwards],
+                        Duration::from_millis(250),
+                    )
+                    .set_repeat(RepeatAnimation::Forever);
             }
 
-            if keyboard_input.pressed(KeyCode::KeyS) {
-                if !is_underwater {
-                    let forward = protagonist_transform.rotation * Vec3::Z;
-                    let backward_flat = Vec3::new(forward.x, 0.0, forward.z).normalize();
-                    
-                    for mut linear_velocity in velocity_query.iter_mut() {
-                        // Preserve existing vertical velocity
-                        let current_y = linear_velocity.0.y;
-                        linear_velocity.0 = backward_flat * move_speed;
-                        linear_velocity.0.y = current_y;
-                    }
-                } else {
-                    // Existing underwater downward movement code
-                    for mut linear_velocity in velocity_query.iter_mut() {
-                        linear_velocity.0 = Vec3::new(0.0, -underwater_speed, 0.0);
-                    }
+            if actions.pressed(Action::Backward) {
+                let forward = protagonist_transform.rotation * Vec3::Z;
+                let backward_flat = Vec3::new(forward.x, 0.0, forward.z).normalize();
+                
+                for mut linear_velocity in velocity_query.iter_mut() {
+                    // Preserve existing vertical velocity
+                    let current_y = linear_velocity.0.y;
+                    linear_velocity.0 = backward_flat * move_speed * stance.speed_multiplier();
+                    linear_velocity.0.y = current_y;
                 }
             }
 
             // Stop movement when S is released
-            if keyboard_input.just_released(KeyCode::KeyS) {
-                if !is_underwater {
-                    let crouch = *SCENES.get("CROUCH").unwrap();
-                    transitions
-                        .play(
-                            &mut player,
-                            animations.animations[crouch],
-                            Duration::from_millis(250),
-                        )
-                        .set_repeat(RepeatAnimation::Count(1));
-                }
+            if actions.just_released(Action::Backward) {
+                play_rest_pose(&mut player, &mut transitions, &animations, stance);
 
                 for mut linear_velocity in velocity_query.iter_mut() {
                     linear_velocity.0 = Vec3::ZERO;
                 }
             }
 
-            // Jump (Space)
-            if keyboard_input.just_pressed(KeyCode::Space) {
+            // Jump (Space), only from standing
+            if actions.just_pressed(Action::Jump) && stance == Stance::Standing {
                 let light_offset = Vec3::new(0.0, 2.0, 0.0);
                 commands.spawn((
                     PointLightBundle {
@@ -434,9 +330,13 @@ pub fn keyboard_animation_control(
                         initial_intensity: BACKPACK_LIGHT_INTENSITY,
                     },
                 ));
+                sounds.send(PlaySound {
+                    cue: SoundCue::JetpackBurst,
+                    position: protagonist_transform.translation,
+                });
             }
 
-            if keyboard_input.just_released(KeyCode::Space) {
+            if actions.just_released(Action::Jump) && stance == Stance::Standing {
                 let legs_up = *SCENES.get("LEGS_UP").unwrap();
 
                 transitions
@@ -452,18 +352,6 @@ pub fn keyboard_animation_control(
                 }
             }
 
-            // Teleport the character 10 units down when V is pressed
-            if keyboard_input.just_pressed(KeyCode::KeyV) {
-                protagonist_transform.translation.y -= 10.0;
-                info!("Teleported 10 units down!");
-            }
-
-            // Teleport the character 15 units up when B is pressed
-            if keyboard_input.just_pressed(KeyCode::KeyB) {
-                protagonist_transform.translation.y += 15.0;
-                info!("Teleported 15 units up!");
-            }
-
             // Toggle lighting with K for night and L for alarm
             if keyboard_input.just_pressed(KeyCode::KeyK) {
                 for mut light in directional_light_query.iter_mut() {
@@ -481,71 +369,15 @@ pub fn keyboard_animation_control(
                 }
             }
 
-            // Switch animations with Tab
-            if keyboard_input.just_pressed(KeyCode::Tab) {
-                *current_animation = (*current_animation + 1) % animations.animations.len();
-                let key = SCENES
-                    .iter()
-                    .find_map(|(k, &v)| if v == *current_animation { Some(k) } else { None });
-                println!(
-                    "Scene {}: {}",
-                    *current_animation,
-                    key.unwrap_or(&"* DISCARDED ANIMATION*")
-                );
+            // Handle placing charge (C). The charge goes down when the crouch's hand touches the ground.
+            // Only on the ground, except in dev builds where it goes down anywhere for testing.
+            if actions.just_pressed(Action::PlaceCharge) && (is_grounded || cfg!(feature = "dev")) {
+                dev_log!("C key pressed, is_grounded: {}", is_grounded);
 
-                transitions
-                    .play(
-                        &mut player,
-                        animations.animations[*current_animation],
-                        Duration::from_millis(250),
-                    )
-                    .repeat();
-            }
-
-            // Handle placing charge (C)
-            if keyboard_input.just_pressed(KeyCode::KeyC) {
-                info!("C key pressed, is_colliding: {}", is_colliding);  // Debug log
-
-                // Remove the collision check temporarily for testing
-                let light_position = protagonist_transform.translation + Vec3::new(0.0, 0.0, 0.0);
-                
-                info!("Placing charge at position: {:?}", light_position);  // Debug log
-                
-                // Fix the material creation with proper type conversion
-                let charge_material = materials.add(StandardMaterial {
-                    emissive: CHARGE_LIGHT_COLOR.into(),  // Convert Color to LinearRgba
-                    base_color: Color::BLACK,
-                    ..default()
+                commands.entity(protagonist).insert(PlacingCharge {
+                    fallback: Timer::from_seconds(CHARGE_PLACE_FALLBACK_SECS, TimerMode::Once),
                 });
 
-                // Create cylinder mesh
-                let charge_mesh = meshes.add(Cylinder {
-                    radius: CHARGE_MESH_RADIUS,
-                    half_height: CHARGE_MESH_HEIGHT / 2.0,
-                    ..default()
-                });
-
-                // Spawn the charge
-                commands.spawn((
-                    PbrBundle {
-                        mesh: charge_mesh,
-                        material: charge_material,
-                        transform: Transform::from_translation(light_position),
-                        ..default()
-                    },
-                    PointLight {
-                        color: CHARGE_LIGHT_COLOR,
-                        intensity: CHARGE_LIGHT_INTENSITY,
-                        range: CHARGE_LIGHT_RANGE,
-                        radius: CHARGE_LIGHT_RADIUS,
-                        shadows_enabled: true,
-                        ..default()
-                    },
-                    BlinkingLight {
-                        timer: Timer::from_seconds(0.5, TimerMode::Repeating),
-                    },
-                ));
-
                 // Play crouch animation
                 let crouch = *SCENES.get("CROUCH").unwrap();
                 transitions
@@ -555,34 +387,15 @@ pub fn keyboard_animation_control(
                         Duration::from_millis(250),
                     )
                     .set_repeat(RepeatAnimation::Count(1));
-
-                info!("Charge placed successfully");  // Debug log
             }
         }
 
         // Reset angular velocity if no rotation keys are pressed
-        if !keyboard_input.pressed(KeyCode::KeyA) && !keyboard_input.pressed(KeyCode::KeyD) {
+        if !actions.pressed(Action::TurnLeft) && !actions.pressed(Action::TurnRight) {
             for mut angular_velocity in angular_velocity_query.iter_mut() {
                 angular_velocity.0 = Vec3::ZERO;
             }
         }
-
-        // Update lighting based on underwater state
-        for mut light in directional_light_query.iter_mut() {
-            let in_water_environment = 
-                protagonist_transform.translation.x > WALL_MIN_X
-                && protagonist_transform.translation.x < WALL_MAX_X
-                && protagonist_transform.translation.z > WALL_MIN_Z
-                && protagonist_transform.translation.z < WALL_MAX_Z;
-
-            if is_underwater {
-                light.illuminance = UNDERWATER_LIGHT_ILLUMINANCE;
-                light.color = UNDERWATER_LIGHT_COLOR;
-            } else {
-                light.illuminance = NORMAL_LIGHT_ILLUMINANCE;
-                light.color = NORMAL_LIGHT_COLOR;
-            }
-        }
     }
 }
 
@@ -595,13 +408,22 @@ pub struct BlinkingLight {
 pub fn blink_lights(
     time: Res<Time>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    mut lights: Query<(Entity, &mut PointLight, &mut BlinkingLight, &Handle<StandardMaterial>)>,
+    mut lights: Query<(&GlobalTransform, &mut PointLight, &mut BlinkingLight, &Handle<StandardMaterial>)>,
+    mut sounds: EventWriter<PlaySound>,
 ) {
-    for (_, mut light, mut blink, material_handle) in &mut lights {
+    for (transform, mut light, mut blink, material_handle) in &mut lights {
         blink.timer.tick(time.delta());
         if blink.timer.just_finished() {
             let is_on = light.intensity > 0.0;
             light.intensity = if is_on { 0.0 } else { CHARGE_LIGHT_INTENSITY };
+
+            // Beep as the light comes on
+            if !is_on {
+                sounds.send(PlaySound {
+                    cue: SoundCue::ChargeBeep,
+                    position: transform.translation(),
+                });
+            }
             
             // Also update the material's emission
             if let Some(material) = materials.get_mut(material_handle) {
@@ -615,6 +437,129 @@ pub fn blink_lights(
     }
 }
 
+// Blow up every placed charge when G is pressed
+pub fn detonate_charges(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    charges: Query<(Entity, &Transform), With<Charge>>,
+    mut explosions: EventWriter<Explosion>,
+) {
+    if !actions.just_pressed(Action::Detonate) {
+        return;
+    }
+
+    for (entity, transform) in &charges {
+        dev_log!("Detonating charge at {:?}", transform.translation);
+        explosions.send(Explosion {
+            position: transform.translation,
+            radius: CHARGE_EXPLOSION_RADIUS,
+            damage: CHARGE_EXPLOSION_DAMAGE,
+        });
+        commands.entity(entity).despawn();
+
+        // Short flash where the charge was
+        commands.spawn((
+            PointLightBundle {
+                point_light: PointLight {
+                    color: CHARGE_LIGHT_COLOR,
+                    intensity: CHARGE_LIGHT_INTENSITY,
+                    range: CHARGE_EXPLOSION_RADIUS * 2.0,
+                    shadows_enabled: true,
+                    ..default()
+                },
+                transform: *transform,
+                ..default()
+            },
+            TemporaryLight {
+                lifetime: Timer::from_seconds(0.5, TimerMode::Once),
+                initial_intensity: CHARGE_LIGHT_INTENSITY,
+            },
+        ));
+    }
+}
+
+// Placed charges can be picked up again with Interact
+pub fn pick_up_charges(
+    mut commands: Commands,
+    mut interacted_events: EventReader<Interacted>,
+    charges: Query<(), With<Charge>>,
+) {
+    for event in interacted_events.read() {
+        if charges.contains(event.target) {
+            dev_log!("Picked up charge {:?}", event.target);
+            commands.entity(event.target).despawn();
+        }
+    }
+}
+
+/// Puts the charge down on the crouch's hand plant marker
+pub fn place_charges(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut marker_events: EventReader<AnimationMarkerReached>,
+    mut placing_query: Query<(Entity, &Transform, &mut PlacingCharge), With<Protagonist>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut game_events: EventWriter<GameEvent>,
+) {
+    let hand_planted = marker_events
+        .read()
+        .any(|event| event.marker == AnimationMarker::HandPlant);
+
+    let Ok((entity, protagonist_transform, mut placing)) = placing_query.get_single_mut() else {
+        return;
+    };
+
+    placing.fallback.tick(time.delta());
+    if !hand_planted && !placing.fallback.finished() {
+        return;
+    }
+    commands.entity(entity).remove::<PlacingCharge>();
+
+    let light_position = protagonist_transform.translation;
+
+    dev_log!("Placing charge at position: {:?}", light_position);
+
+    // Fix the material creation with proper type conversion
+    let charge_material = materials.add(StandardMaterial {
+        emissive: CHARGE_LIGHT_COLOR.into(),  // Convert Color to LinearRgba
+        base_color: Color::BLACK,
+        ..default()
+    });
+
+    // Create cylinder mesh
+    let charge_mesh = meshes.add(Cylinder {
+        radius: CHARGE_MESH_RADIUS,
+        half_height: CHARGE_MESH_HEIGHT / 2.0,
+    });
+
+    // Spawn the charge
+    commands.spawn((
+        PbrBundle {
+            mesh: charge_mesh,
+            material: charge_material,
+            transform: Transform::from_translation(light_position),
+            ..default()
+        },
+        PointLight {
+            color: CHARGE_LIGHT_COLOR,
+            intensity: CHARGE_LIGHT_INTENSITY,
+            range: CHARGE_LIGHT_RANGE,
+            radius: CHARGE_LIGHT_RADIUS,
+            shadows_enabled: true,
+            ..default()
+        },
+        BlinkingLight {
+            timer: Timer::from_seconds(0.5, TimerMode::Repeating),
+        },
+        Charge,
+        Interactable::new("Pick up beacon", CHARGE_PICKUP_RANGE).with_facing(CHARGE_PICKUP_ANGLE),
+    ));
+    game_events.send(GameEvent::BeaconPlaced { position: light_position });
+
+    dev_log!("Charge placed successfully");
+}
+
 // Add this new system
 pub fn handle_temporary_lights(
     mut commands: Commands,
@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+use avian3d::prelude::*;
+use crate::components::Protagonist;
+use crate::systems::actions::{Action, ActionState};
+use crate::systems::health::Dead;
+
+// Rays for the line of sight check start this far above the protagonist's feet
+const EYE_HEIGHT: f32 = 1.5;
+// How much a target straight ahead is preferred over a closer one off to the side
+const FACING_WEIGHT: f32 = 2.0;
+
+/// Anything the protagonist can use with the Interact action
+#[derive(Component)]
+pub struct Interactable {
+    pub prompt: String,
+    pub range: f32,
+    // The protagonist has to be facing the target within this angle (radians), `None` for any direction
+    pub max_facing_angle: Option<f32>,
+}
+
+impl Interactable {
+    pub fn new(prompt: impl Into<String>, range: f32) -> Self {
+        Self {
+            prompt: prompt.into(),
+            range,
+            max_facing_angle: None,
+        }
+    }
+
+    pub fn with_facing(mut self, max_angle: f32) -> Self {
+        self.max_facing_angle = Some(max_angle);
+        self
+    }
+}
+
+// The interactable the prompt is currently shown for
+#[derive(Resource, Default)]
+pub struct InteractionTarget(pub Option<Entity>);
+
+#[derive(Event)]
+pub struct Interacted {
+    pub actor: Entity,
+    pub target: Entity,
+}
+
+/// Picks the closest interactable in range that the protagonist is facing and can see
+#[allow(clippy::type_complexity)]
+pub fn select_interaction_target(
+    spatial_query: SpatialQuery,
+    protagonist_query: Query<(Entity, &Transform), (With<Protagonist>, Without<Dead>)>,
+    interactables: Query<(Entity, &GlobalTransform, &Interactable)>,
+    collider_parents: Query<&ColliderParent>,
+    mut target: ResMut<InteractionTarget>,
+) {
+    let best = protagonist_query.get_single().ok().and_then(|(protagonist, transform)| {
+        let eye = transform.translation + Vec3::Y * EYE_HEIGHT;
+        let forward = -(transform.rotation * Vec3::Z);
+        let forward_flat = Vec3::new(forward.x, 0.0, forward.z).normalize_or_zero();
+
+        interactables
+            .iter()
+            .filter_map(|(entity, global_transform, interactable)| {
+                let to_target = global_transform.translation() - transform.translation;
+                let distance = to_target.length();
+                if distance > interactable.range {
+                    return None;
+                }
+
+                let to_target_flat = Vec3::new(to_target.x, 0.0, to_target.z);
+                let angle = if to_target_flat.length_squared() > f32::EPSILON {
+                    forward_flat.angle_between(to_target_flat)
+                } else {
+                    // Right underneath us counts as facing it
+                    0.0
+                };
+                if interactable.max_facing_angle.is_some_and(|max_angle| angle > max_angle) {
+                    return None;
+                }
+
+                // Something else in the way? Hits on the target's own child colliders are fine.
+                let to_eye_target = global_transform.translation() - eye;
+                if let Ok(direction) = Dir3::new(to_eye_target) {
+                    let blocked = spatial_query
+                        .cast_ray(
+                            eye,
+                            direction,
+                            to_eye_target.length(),
+                            true,
+                            SpatialQueryFilter::from_excluded_entities([protagonist]),
+                        )
+                        .is_some_and(|hit| {
+                            hit.entity != entity
+                                && collider_parents.get(hit.entity).map_or(true, |parent| parent.get() != entity)
+                        });
+                    if blocked {
+                        return None;
+                    }
+                }
+
+                Some((entity, distance + angle * FACING_WEIGHT))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(entity, _)| entity)
+    });
+
+    // Only write when it changes so the HUD can rely on change detection
+    if target.0 != best {
+        target.0 = best;
+    }
+}
+
+pub fn interact_system(
+    actions: Res<ActionState>,
+    target: Res<InteractionTarget>,
+    protagonist_query: Query<Entity, (With<Protagonist>, Without<Dead>)>,
+    mut interacted_events: EventWriter<Interacted>,
+) {
+    if !actions.just_pressed(Action::Interact) {
+        return;
+    }
+
+    if let (Some(target), Ok(actor)) = (target.0, protagonist_query.get_single()) {
+        interacted_events.send(Interacted { actor, target });
+    }
+}
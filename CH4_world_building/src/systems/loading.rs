@@ -0,0 +1,216 @@
+use bevy::{
+    asset::{RecursiveDependencyLoadState, UntypedAssetId},
+    prelude::*,
+};
+use avian3d::prelude::*;
+use crate::components::Protagonist;
+use crate::systems::baked_colliders::BakedColliderHierarchy;
+
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum GameState {
+    // Waiting on assets, colliders and the protagonist's animations, physics is paused
+    #[default]
+    Loading,
+    Playing,
+}
+
+/// Systems that move things around or react to the player, they wait for `Playing`
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Gameplay;
+
+/// How many of the things the level waits on are ready
+#[derive(Resource, Default, Debug)]
+pub struct LoadingProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+// Marker for the loading screen, gone once everything's in
+#[derive(Component)]
+pub struct LoadingScreen;
+
+#[derive(Component)]
+pub struct LoadingText;
+
+#[derive(Component)]
+pub struct LoadingBar;
+
+/// Pauses physics and covers the screen until loading is done
+pub fn setup_loading_screen(mut commands: Commands, mut physics_time: ResMut<Time<Physics>>) {
+    physics_time.pause();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(16.0),
+                    ..default()
+                },
+                background_color: Color::BLACK.into(),
+                // Above the HUD
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            LoadingScreen,
+        ))
+        .with_children(|screen| {
+            screen.spawn((
+                TextBundle::from_section(
+                    "Loading",
+                    TextStyle {
+                        font_size: 32.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                LoadingText,
+            ));
+            screen
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(400.0),
+                        height: Val::Px(12.0),
+                        ..default()
+                    },
+                    background_color: Color::srgb(0.2, 0.2, 0.2).into(),
+                    ..default()
+                })
+                .with_children(|track| {
+                    track.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(0.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: Color::WHITE.into(),
+                            ..default()
+                        },
+                        LoadingBar,
+                    ));
+                });
+        });
+}
+
+// Loaded, or failed, which the asset server has already complained about. Either
+// way there's nothing left to wait for. Handles that weren't loaded from a file
+// have no state and are always ready.
+fn is_settled(asset_server: &AssetServer, id: impl Into<UntypedAssetId>) -> bool {
+    !matches!(
+        asset_server.get_recursive_dependency_load_state(id),
+        Some(RecursiveDependencyLoadState::NotLoaded | RecursiveDependencyLoadState::Loading)
+    )
+}
+
+fn has_failed(asset_server: &AssetServer, id: impl Into<UntypedAssetId>) -> bool {
+    matches!(
+        asset_server.get_recursive_dependency_load_state(id),
+        Some(RecursiveDependencyLoadState::Failed)
+    )
+}
+
+/// Counts up the level's scenes, textures and colliders, and starts the game once
+/// they're all there and the protagonist's animations are set up
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn track_loading(
+    asset_server: Res<AssetServer>,
+    materials: Res<Assets<StandardMaterial>>,
+    scenes: Query<&Handle<Scene>>,
+    material_handles: Query<&Handle<StandardMaterial>>,
+    environment_maps: Query<&EnvironmentMapLight>,
+    collider_hierarchies: Query<
+        Option<&Handle<Scene>>,
+        Or<(With<ColliderConstructorHierarchy>, With<BakedColliderHierarchy>)>,
+    >,
+    collider_constructors: Query<(), With<ColliderConstructor>>,
+    players: Query<(), With<AnimationTransitions>>,
+    protagonist_scenes: Query<&Handle<Scene>, With<Protagonist>>,
+    mut progress: ResMut<LoadingProgress>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let mut ready: Vec<bool> = Vec::new();
+
+    ready.extend(scenes.iter().map(|scene| is_settled(&asset_server, scene)));
+    for handle in &material_handles {
+        let Some(material) = materials.get(handle) else {
+            ready.push(is_settled(&asset_server, handle));
+            continue;
+        };
+        let textures = [
+            &material.base_color_texture,
+            &material.normal_map_texture,
+            &material.emissive_texture,
+            &material.metallic_roughness_texture,
+            &material.occlusion_texture,
+        ];
+        ready.extend(textures.into_iter().flatten().map(|texture| is_settled(&asset_server, texture)));
+    }
+    for environment_map in &environment_maps {
+        ready.push(is_settled(&asset_server, &environment_map.diffuse_map));
+        ready.push(is_settled(&asset_server, &environment_map.specular_map));
+    }
+
+    // Hierarchies are removed once their colliders are made, unless the scene never
+    // loaded, then there won't be any
+    ready.extend(
+        collider_hierarchies
+            .iter()
+            .map(|scene| scene.is_some_and(|scene| has_failed(&asset_server, scene))),
+    );
+    ready.extend(collider_constructors.iter().map(|_| false));
+    // The animation player comes with the protagonist's scene, so if that failed
+    // there's no rig to wait for
+    let is_rig_missing = players.is_empty()
+        && protagonist_scenes.iter().any(|scene| has_failed(&asset_server, scene));
+    ready.push(!players.is_empty() || is_rig_missing);
+
+    let done = ready.iter().filter(|&&ready| ready).count();
+    if progress.done != done || progress.total != ready.len() {
+        progress.done = done;
+        progress.total = ready.len();
+    }
+    if done == ready.len() {
+        if is_rig_missing {
+            warn!("The protagonist's scene didn't load, starting without its rig or animations");
+        }
+        next_state.set(GameState::Playing);
+    }
+}
+
+pub fn update_loading_screen(
+    progress: Res<LoadingProgress>,
+    mut text_query: Query<&mut Text, With<LoadingText>>,
+    mut bar_query: Query<&mut Style, With<LoadingBar>>,
+) {
+    if !progress.is_changed() {
+        return;
+    }
+
+    let fraction = progress.done as f32 / progress.total.max(1) as f32;
+    for mut text in &mut text_query {
+        text.sections[0].value = format!("Loading {}/{}", progress.done, progress.total);
+    }
+    for mut style in &mut bar_query {
+        style.width = Val::Percent(fraction * 100.0);
+    }
+}
+
+/// Everything's in, so physics starts and the screen goes
+pub fn finish_loading(
+    mut commands: Commands,
+    mut physics_time: ResMut<Time<Physics>>,
+    progress: Res<LoadingProgress>,
+    screen_query: Query<Entity, With<LoadingScreen>>,
+) {
+    physics_time.unpause();
+    for screen in &screen_query {
+        commands.entity(screen).despawn_recursive();
+    }
+    info!("Loaded {} assets and colliders", progress.total);
+}
@@ -0,0 +1,210 @@
+use bevy::prelude::*;
+use petgraph::Direction;
+use serde::Deserialize;
+use crate::level::read_ron_asset;
+use crate::systems::props::{PropKind, PropRegistry};
+use crate::resources::{Animations, PROTAGONIST_GLB, SCENES};
+
+use std::collections::HashMap;
+
+pub const ANIMATION_EVENTS_PATH: &str = "animations/events.ron";
+
+// During a crossfade only animations at least this strong fire their markers,
+// so a footstep isn't heard twice from the outgoing and incoming clips
+const MIN_MARKER_WEIGHT: f32 = 0.5;
+
+const DUST_PUFF_SECS: f32 = 0.6;
+const DUST_PUFF_GROWTH: f32 = 2.5;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnimationMarker {
+    Footstep,
+    // Hand touching the ground, the charge goes down here
+    HandPlant,
+    Landing,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct MarkerTime {
+    // Fraction of the way through the clip, 0 to 1
+    pub at: f32,
+    pub marker: AnimationMarker,
+}
+
+// Fired when a playing clip crosses one of its markers
+#[derive(Event, Debug, Clone, Copy)]
+pub struct AnimationMarkerReached {
+    // Entity with the `AnimationPlayer`, inside the protagonist's scene
+    pub player: Entity,
+    pub marker: AnimationMarker,
+}
+
+/// Markers per clip, read from `assets/animations/events.ron` which is keyed by the
+/// names in `SCENES`. Kept by clip asset so they work for every graph node playing it.
+#[derive(Resource, Default)]
+pub struct AnimationMarkers {
+    by_clip: HashMap<AssetId<AnimationClip>, Vec<MarkerTime>>,
+}
+
+impl AnimationMarkers {
+    pub fn load(path: &str, asset_server: &AssetServer) -> Self {
+        let authored: HashMap<String, Vec<MarkerTime>> = read_ron_asset(path).unwrap_or_default();
+        let mut by_clip = HashMap::new();
+
+        for (name, markers) in authored {
+            let Some(&index) = SCENES.get(name.as_str()) else {
+                warn!("Animation events for unknown clip {}", name);
+                continue;
+            };
+            let handle: Handle<AnimationClip> =
+                asset_server.load(GltfAssetLabel::Animation(index).from_asset(PROTAGONIST_GLB));
+            by_clip.insert(handle.id(), markers);
+        }
+
+        Self { by_clip }
+    }
+}
+
+// Where each playing animation was last frame, on the entity with the `AnimationPlayer`
+#[derive(Component, Default)]
+pub struct MarkerTracker {
+    last_seek: HashMap<AnimationNodeIndex, f32>,
+}
+
+// How much of a graph node ends up in the pose, its own weight times its parents'
+fn graph_weight(graph: &AnimationGraph, node: AnimationNodeIndex) -> f32 {
+    let mut weight = 1.0;
+    let mut current = node;
+    loop {
+        weight *= graph.get(current).map_or(1.0, |graph_node| graph_node.weight);
+        match graph.graph.neighbors_directed(current, Direction::Incoming).next() {
+            Some(parent) => current = parent,
+            None => return weight,
+        }
+    }
+}
+
+// Markers passed going from `from` to `to` seconds, wrapping round the end on a loop
+fn crossed(markers: &[MarkerTime], duration: f32, from: Option<f32>, to: f32) -> Vec<AnimationMarker> {
+    let in_range = |low: f32, high: f32, include_low: bool| {
+        markers
+            .iter()
+            .filter(move |marker| {
+                let time = marker.at * duration;
+                (time > low || (include_low && time == low)) && time <= high
+            })
+            .map(|marker| marker.marker)
+    };
+
+    match from {
+        // Just started, anything at the very start counts
+        None => in_range(0.0, to, true).collect(),
+        Some(from) if to >= from => in_range(from, to, false).collect(),
+        Some(from) => in_range(from, duration, false).chain(in_range(0.0, to, true)).collect(),
+    }
+}
+
+/// Fires `AnimationMarkerReached` for every marker the playing clips went past this frame
+pub fn fire_animation_markers(
+    mut commands: Commands,
+    markers: Res<AnimationMarkers>,
+    animations: Res<Animations>,
+    graphs: Res<Assets<AnimationGraph>>,
+    clips: Res<Assets<AnimationClip>>,
+    mut players: Query<(Entity, &AnimationPlayer, Option<&mut MarkerTracker>)>,
+    mut marker_events: EventWriter<AnimationMarkerReached>,
+) {
+    let Some(graph) = graphs.get(&animations.graph) else {
+        return;
+    };
+
+    for (entity, player, tracker) in &mut players {
+        let Some(mut tracker) = tracker else {
+            commands.entity(entity).insert(MarkerTracker::default());
+            continue;
+        };
+
+        let mut seen = HashMap::new();
+        for (&node, active) in player.playing_animations() {
+            let seek = active.seek_time();
+            seen.insert(node, seek);
+
+            let Some(handle) = graph.get(node).and_then(|graph_node| graph_node.clip.as_ref()) else {
+                continue;
+            };
+            let (Some(clip_markers), Some(clip)) = (markers.by_clip.get(&handle.id()), clips.get(handle)) else {
+                continue;
+            };
+            if active.is_paused() || active.weight() * graph_weight(graph, node) < MIN_MARKER_WEIGHT {
+                continue;
+            }
+
+            let from = tracker.last_seek.get(&node).copied();
+            for marker in crossed(clip_markers, clip.duration(), from, seek) {
+                marker_events.send(AnimationMarkerReached { player: entity, marker });
+            }
+        }
+
+        // Animations that stopped start again from scratch next time
+        tracker.last_seek = seen;
+    }
+}
+
+#[derive(Component)]
+pub struct DustPuff {
+    timer: Timer,
+    size: f32,
+}
+
+/// Kicks up a little dust for footsteps and landings
+pub fn spawn_dust_puffs(
+    mut commands: Commands,
+    mut marker_events: EventReader<AnimationMarkerReached>,
+    parents: Query<&Parent>,
+    transforms: Query<&GlobalTransform>,
+    props: Res<PropRegistry>,
+) {
+    for event in marker_events.read() {
+        let size = match event.marker {
+            AnimationMarker::Footstep => 0.15,
+            AnimationMarker::Landing => 0.4,
+            AnimationMarker::HandPlant => continue,
+        };
+
+        // The player sits somewhere inside the protagonist's scene, use the top of it
+        let root = parents.iter_ancestors(event.player).last().unwrap_or(event.player);
+        let Ok(feet) = transforms.get(root).map(|transform| transform.translation()) else {
+            continue;
+        };
+
+        // Just the looks, dust doesn't collide
+        let puff = props.get(PropKind::DustPuff);
+        commands.spawn((
+            PbrBundle {
+                mesh: puff.mesh.clone(),
+                material: puff.material.clone(),
+                transform: Transform::from_translation(feet).with_scale(Vec3::splat(size)),
+                ..default()
+            },
+            DustPuff {
+                timer: Timer::from_seconds(DUST_PUFF_SECS, TimerMode::Once),
+                size,
+            },
+        ));
+    }
+}
+
+pub fn update_dust_puffs(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut puffs: Query<(Entity, &mut Transform, &mut DustPuff)>,
+) {
+    for (entity, mut transform, mut puff) in &mut puffs {
+        puff.timer.tick(time.delta());
+        transform.scale = Vec3::splat(puff.size * (1.0 + puff.timer.fraction() * DUST_PUFF_GROWTH));
+
+        if puff.timer.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
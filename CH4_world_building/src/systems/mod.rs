@@ -1,4 +1,35 @@
 pub mod setup;
 pub mod camera;
 pub mod input;
-pub mod portal;
\ No newline at end of file
+pub mod portal;
+pub mod protagonist;
+pub mod health;
+pub mod checkpoint;
+pub mod objectives;
+pub mod hud;
+pub mod actions;
+pub mod interaction;
+pub mod climb;
+pub mod stance;
+pub mod environment;
+pub mod swim;
+pub mod controller;
+pub mod root_motion;
+pub mod blend;
+pub mod markers;
+pub mod audio;
+pub mod replay;
+pub mod placeholders;
+pub mod loading;
+pub mod rocks;
+pub mod scatter;
+pub mod baked_colliders;
+pub mod props;
+#[cfg(feature = "dev")]
+pub mod debug;
+#[cfg(feature = "dev")]
+pub mod console;
+#[cfg(feature = "dev")]
+pub mod dev_commands;
+#[cfg(feature = "dev")]
+pub mod cheats;
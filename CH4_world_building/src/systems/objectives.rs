@@ -0,0 +1,354 @@
+use bevy::prelude::*;
+use petgraph::{
+    algo::toposort,
+    graph::{DiGraph, NodeIndex},
+    Direction,
+};
+use serde::Deserialize;
+
+use crate::components::{Protagonist, Starship};
+use crate::events::{GameEvent, PortalKind};
+use crate::level::read_ron_asset;
+use crate::systems::checkpoint::CheckpointReached;
+
+use std::collections::HashMap;
+
+// Objective graph loaded at startup, relative to the assets folder
+pub const OBJECTIVES_PATH: &str = "levels/objectives.ron";
+
+// How close (horizontally) counts as having reached the starship
+const STARSHIP_REACH: f32 = 10.0;
+
+// What has to happen in the world for an objective to complete
+#[derive(Debug, Clone, Deserialize)]
+pub enum ObjectiveTrigger {
+    ReachStarship,
+    TraversePortal(PortalKind),
+    // A beacon placed anywhere inside the box
+    PlaceBeacon { min: [f32; 3], max: [f32; 3] },
+    ReachCheckpoint(String),
+}
+
+impl ObjectiveTrigger {
+    fn matches_game_event(&self, event: &GameEvent) -> bool {
+        match (self, event) {
+            (ObjectiveTrigger::ReachStarship, GameEvent::ReachedStarship) => true,
+            (ObjectiveTrigger::TraversePortal(wanted), GameEvent::PortalTraversed(portal)) => wanted == portal,
+            (ObjectiveTrigger::PlaceBeacon { min, max }, GameEvent::BeaconPlaced { position }) => {
+                position.cmpge(Vec3::from_array(*min)).all() && position.cmple(Vec3::from_array(*max)).all()
+            }
+            _ => false,
+        }
+    }
+}
+
+// One entry of the objectives file
+#[derive(Debug, Deserialize)]
+struct ObjectiveData {
+    id: String,
+    title: String,
+    trigger: ObjectiveTrigger,
+    #[serde(default)]
+    requires: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectiveStatus {
+    // Waiting on at least one prerequisite
+    Locked,
+    Active,
+    Completed,
+}
+
+#[derive(Debug)]
+pub struct Objective {
+    pub id: String,
+    pub title: String,
+    pub trigger: ObjectiveTrigger,
+    pub status: ObjectiveStatus,
+}
+
+#[derive(Event)]
+pub struct ObjectiveCompleted {
+    pub id: String,
+}
+
+/// The objective DAG. Edges point from a prerequisite to the objectives it unlocks.
+#[derive(Resource, Default)]
+pub struct Objectives {
+    graph: DiGraph<Objective, ()>,
+    by_id: HashMap<String, NodeIndex>,
+}
+
+impl Objectives {
+    /// Loads the objective graph, or an empty one if the file is missing or invalid
+    pub fn load(path: &str) -> Self {
+        let Some(data) = read_ron_asset::<Vec<ObjectiveData>>(path) else {
+            return Self::default();
+        };
+
+        match Self::from_data(data) {
+            Ok(objectives) => objectives,
+            Err(err) => {
+                warn!("Invalid objectives in {}: {}", path, err);
+                Self::default()
+            }
+        }
+    }
+
+    /// Parses and checks an objectives file without loading it, for `check-assets`
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        let data: Vec<ObjectiveData> = ron::from_str(text).map_err(|err| err.to_string())?;
+        Self::from_data(data)
+    }
+
+    fn from_data(data: Vec<ObjectiveData>) -> Result<Self, String> {
+        let mut objectives = Self::default();
+        let mut requirements = Vec::new();
+
+        for entry in data {
+            if objectives.by_id.contains_key(&entry.id) {
+                return Err(format!("duplicate objective id {}", entry.id));
+            }
+
+            let node = objectives.graph.add_node(Objective {
+                id: entry.id.clone(),
+                title: entry.title,
+                trigger: entry.trigger,
+                status: ObjectiveStatus::Locked,
+            });
+            objectives.by_id.insert(entry.id, node);
+            requirements.push((node, entry.requires));
+        }
+
+        for (node, requires) in requirements {
+            for required in requires {
+                let Some(&prerequisite) = objectives.by_id.get(&required) else {
+                    return Err(format!("{} requires unknown objective {}", objectives.graph[node].id, required));
+                };
+                objectives.graph.add_edge(prerequisite, node, ());
+            }
+        }
+
+        if let Err(cycle) = toposort(&objectives.graph, None) {
+            return Err(format!("prerequisites form a cycle through {}", objectives.graph[cycle.node_id()].id));
+        }
+
+        // Everything without prerequisites is available straight away
+        let roots: Vec<NodeIndex> = objectives
+            .graph
+            .node_indices()
+            .filter(|&node| objectives.prerequisites_met(node))
+            .collect();
+        for node in roots {
+            objectives.graph[node].status = ObjectiveStatus::Active;
+        }
+
+        Ok(objectives)
+    }
+
+    fn prerequisites_met(&self, node: NodeIndex) -> bool {
+        self.graph
+            .neighbors_directed(node, Direction::Incoming)
+            .all(|prerequisite| self.graph[prerequisite].status == ObjectiveStatus::Completed)
+    }
+
+    /// Marks an active objective as done and unlocks whatever now has all its prerequisites.
+    /// Returns false if the objective doesn't exist or isn't active.
+    pub fn complete(&mut self, id: &str) -> bool {
+        let Some(&node) = self.by_id.get(id) else {
+            return false;
+        };
+        if self.graph[node].status != ObjectiveStatus::Active {
+            return false;
+        }
+
+        self.graph[node].status = ObjectiveStatus::Completed;
+
+        let dependents: Vec<NodeIndex> = self.graph.neighbors_directed(node, Direction::Outgoing).collect();
+        for dependent in dependents {
+            if self.graph[dependent].status == ObjectiveStatus::Locked && self.prerequisites_met(dependent) {
+                self.graph[dependent].status = ObjectiveStatus::Active;
+                info!("Objective unlocked: {}", self.graph[dependent].title);
+            }
+        }
+
+        true
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Objective> {
+        self.by_id.get(id).map(|&node| &self.graph[node])
+    }
+
+    pub fn status(&self, id: &str) -> Option<ObjectiveStatus> {
+        self.get(id).map(|objective| objective.status)
+    }
+
+    /// All objectives in file order
+    pub fn iter(&self) -> impl Iterator<Item = &Objective> {
+        self.graph.node_weights()
+    }
+
+    /// Objectives the player can currently work on, in file order
+    pub fn active(&self) -> impl Iterator<Item = &Objective> {
+        self.iter().filter(|objective| objective.status == ObjectiveStatus::Active)
+    }
+
+    pub fn completed(&self) -> impl Iterator<Item = &Objective> {
+        self.iter().filter(|objective| objective.status == ObjectiveStatus::Completed)
+    }
+
+    /// The objectives that have to be completed before `id` unlocks
+    pub fn prerequisites(&self, id: &str) -> impl Iterator<Item = &Objective> {
+        self.by_id
+            .get(id)
+            .into_iter()
+            .flat_map(|&node| self.graph.neighbors_directed(node, Direction::Incoming))
+            .map(|node| &self.graph[node])
+    }
+
+    pub fn all_completed(&self) -> bool {
+        self.iter().all(|objective| objective.status == ObjectiveStatus::Completed)
+    }
+}
+
+/// Completes active objectives whose triggers match this frame's game events
+pub fn update_objectives(
+    mut objectives: ResMut<Objectives>,
+    mut game_events: EventReader<GameEvent>,
+    mut checkpoint_events: EventReader<CheckpointReached>,
+    mut completed_events: EventWriter<ObjectiveCompleted>,
+) {
+    let mut finished = Vec::new();
+
+    for event in game_events.read() {
+        finished.extend(
+            objectives
+                .active()
+                .filter(|objective| objective.trigger.matches_game_event(event))
+                .map(|objective| objective.id.clone()),
+        );
+    }
+
+    for event in checkpoint_events.read() {
+        finished.extend(
+            objectives
+                .active()
+                .filter(|objective| matches!(&objective.trigger, ObjectiveTrigger::ReachCheckpoint(name) if *name == event.name))
+                .map(|objective| objective.id.clone()),
+        );
+    }
+
+    // Only touch the resource when something actually changed, the HUD watches it
+    if finished.is_empty() {
+        return;
+    }
+
+    for id in finished {
+        if objectives.complete(&id) {
+            info!("Objective completed: {}", id);
+            completed_events.send(ObjectiveCompleted { id });
+        }
+    }
+
+    if objectives.all_completed() {
+        info!("All objectives completed");
+    }
+}
+
+/// Sends `GameEvent::ReachedStarship` whenever the protagonist walks up to the starship
+pub fn starship_proximity_system(
+    protagonist_query: Query<&Transform, With<Protagonist>>,
+    starship_query: Query<&Transform, With<Starship>>,
+    mut game_events: EventWriter<GameEvent>,
+    mut was_near: Local<bool>,
+) {
+    let Ok(protagonist_transform) = protagonist_query.get_single() else {
+        return;
+    };
+
+    let is_near = starship_query.iter().any(|starship_transform| {
+        let offset = starship_transform.translation - protagonist_transform.translation;
+        Vec2::new(offset.x, offset.z).length() < STARSHIP_REACH
+    });
+
+    if is_near && !*was_near {
+        game_events.send(GameEvent::ReachedStarship);
+    }
+    *was_near = is_near;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two ways into the aquifer, both needed before the starship
+    const OBJECTIVES: &str = r#"[
+        (id: "top", title: "Take the top portal", trigger: TraversePortal(Top)),
+        (id: "bottom", title: "Take the bottom portal", trigger: TraversePortal(Bottom)),
+        (id: "ship", title: "Reach the starship", trigger: ReachStarship, requires: ["top", "bottom"]),
+    ]"#;
+
+    fn active_ids(objectives: &Objectives) -> Vec<&str> {
+        objectives.active().map(|objective| objective.id.as_str()).collect()
+    }
+
+    #[test]
+    fn objectives_without_prerequisites_start_active() {
+        let objectives = Objectives::from_ron(OBJECTIVES).unwrap();
+
+        assert_eq!(active_ids(&objectives), ["top", "bottom"]);
+        assert_eq!(objectives.status("ship"), Some(ObjectiveStatus::Locked));
+        let prerequisites: Vec<&str> = objectives.prerequisites("ship").map(|objective| objective.id.as_str()).collect();
+        assert_eq!(prerequisites.len(), 2);
+    }
+
+    #[test]
+    fn completing_every_prerequisite_unlocks_the_dependent() {
+        let mut objectives = Objectives::from_ron(OBJECTIVES).unwrap();
+
+        assert!(objectives.complete("top"));
+        assert_eq!(objectives.status("ship"), Some(ObjectiveStatus::Locked), "unlocked with one prerequisite left");
+
+        assert!(objectives.complete("bottom"));
+        assert_eq!(active_ids(&objectives), ["ship"]);
+
+        assert!(objectives.complete("ship"));
+        assert!(objectives.all_completed());
+    }
+
+    #[test]
+    fn only_active_objectives_complete() {
+        let mut objectives = Objectives::from_ron(OBJECTIVES).unwrap();
+
+        assert!(!objectives.complete("ship"), "completed while locked");
+        assert!(!objectives.complete("missing"));
+        assert!(objectives.complete("top"));
+        assert!(!objectives.complete("top"), "completed twice");
+        assert_eq!(objectives.completed().count(), 1);
+    }
+
+    #[test]
+    fn cycles_are_rejected() {
+        let cycle = r#"[
+            (id: "a", title: "A", trigger: ReachStarship, requires: ["c"]),
+            (id: "b", title: "B", trigger: ReachStarship, requires: ["a"]),
+            (id: "c", title: "C", trigger: ReachStarship, requires: ["b"]),
+        ]"#;
+
+        let err = Objectives::from_ron(cycle).err().expect("cycle accepted");
+        assert!(err.contains("cycle"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn unknown_and_duplicate_ids_are_rejected() {
+        let unknown = r#"[(id: "a", title: "A", trigger: ReachStarship, requires: ["nope"])]"#;
+        let duplicate = r#"[
+            (id: "a", title: "A", trigger: ReachStarship),
+            (id: "a", title: "A again", trigger: ReachStarship),
+        ]"#;
+
+        assert!(Objectives::from_ron(unknown).is_err());
+        assert!(Objectives::from_ron(duplicate).is_err());
+    }
+}
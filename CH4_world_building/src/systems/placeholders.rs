@@ -0,0 +1,459 @@
+// Stand-ins for the downloadable assets. The textures, models and sounds live in a
+// shared folder rather than the repo, so a fresh clone has none of them. Instead of
+// failing to load, missing ones are generated here with a warning for each.
+
+use bevy::{
+    asset::io::{
+        AssetReader,
+        AssetReaderError,
+        AssetSource,
+        AssetSourceId,
+        ErasedAssetReader,
+        PathStream,
+        Reader,
+        VecReader,
+    },
+    prelude::*,
+    render::mesh::{MeshVertexAttribute, VertexAttributeValues},
+};
+use serde_json::{json, Value};
+use crate::resources::{PROTAGONIST_ANIMATIONS, PROTAGONIST_GLB, SCENES, STARSHIP_GLB};
+use crate::systems::stance::FEET_OFFSET;
+
+use std::path::Path;
+
+const CHECKERBOARD_SIZE: u32 = 256;
+const CHECKER_SIZE: u32 = 32;
+
+/// Makes the default asset source fall back to placeholders, has to happen before
+/// `AssetPlugin` is added
+pub fn register_placeholder_source(app: &mut App) {
+    app.register_asset_source(
+        AssetSourceId::Default,
+        AssetSource::build().with_reader(|| {
+            Box::new(PlaceholderReader(AssetSource::get_default_reader("assets".to_string())()))
+        }),
+    );
+}
+
+/// Reads from the usual place, and generates whatever isn't there
+pub struct PlaceholderReader(Box<dyn ErasedAssetReader>);
+
+impl AssetReader for PlaceholderReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<Box<Reader<'a>>, AssetReaderError> {
+        match self.0.read(path).await {
+            Err(AssetReaderError::NotFound(missing)) => match placeholder(path) {
+                Some(bytes) => Ok(Box::new(VecReader::new(bytes))),
+                None => {
+                    warn!("{} is missing, and there's no placeholder for it", path.display());
+                    Err(AssetReaderError::NotFound(missing))
+                }
+            },
+            result => result,
+        }
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<Box<Reader<'a>>, AssetReaderError> {
+        self.0.read_meta(path).await
+    }
+
+    async fn read_directory<'a>(&'a self, path: &'a Path) -> Result<Box<PathStream>, AssetReaderError> {
+        self.0.read_directory(path).await
+    }
+
+    async fn is_directory<'a>(&'a self, path: &'a Path) -> Result<bool, AssetReaderError> {
+        self.0.is_directory(path).await
+    }
+}
+
+fn placeholder(path: &Path) -> Option<Vec<u8>> {
+    let name = path.to_string_lossy().replace('\\', "/");
+    let (kind, bytes) = match name.as_str() {
+        PROTAGONIST_GLB => ("capsule protagonist", protagonist_glb()),
+        STARSHIP_GLB => ("box ship", starship_glb()),
+        _ if name.ends_with(".png") => ("checkerboard", checkerboard_png(&name)),
+        _ if name.ends_with(".ogg") => ("silent sound", silent_ogg()),
+        _ => return None,
+    };
+    warn!("{} is missing, using a placeholder {}", name, kind);
+    Some(bytes)
+}
+
+// Textures
+
+// Two shades of a colour picked from the path, so different textures stay apart
+fn checkerboard_png(path: &str) -> Vec<u8> {
+    let hash = path.bytes().fold(2166136261u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(16777619));
+    let light = [hash as u8 | 0x80, (hash >> 8) as u8 | 0x80, (hash >> 16) as u8 | 0x80];
+    let dark = light.map(|channel| channel / 3);
+
+    let mut rows = Vec::new();
+    for y in 0..CHECKERBOARD_SIZE {
+        // No filter on the row
+        rows.push(0);
+        for x in 0..CHECKERBOARD_SIZE {
+            let is_light = (x / CHECKER_SIZE + y / CHECKER_SIZE).is_multiple_of(2);
+            rows.extend_from_slice(if is_light { &light } else { &dark });
+        }
+    }
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&CHECKERBOARD_SIZE.to_be_bytes());
+    header.extend_from_slice(&CHECKERBOARD_SIZE.to_be_bytes());
+    // 8 bits per channel, RGB, default compression, filtering and no interlacing
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    png_chunk(&mut png, b"IHDR", &header);
+    png_chunk(&mut png, b"IDAT", &zlib_stored(&rows));
+    png_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(kind.iter().chain(data));
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+// Deflate without compression, which every PNG reader has to handle
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = data.chunks(u16::MAX as usize).collect();
+    for (i, block) in blocks.iter().enumerate() {
+        let is_last = i + 1 == blocks.len();
+        out.push(is_last as u8);
+        let length = block.len() as u16;
+        out.extend_from_slice(&length.to_le_bytes());
+        out.extend_from_slice(&(!length).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    out.extend_from_slice(&((b << 16) | a).to_be_bytes());
+    out
+}
+
+fn crc32<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    !bytes.fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| {
+            if crc & 1 == 1 { (crc >> 1) ^ 0xedb88320 } else { crc >> 1 }
+        })
+    })
+}
+
+// Sounds
+
+const SILENCE_RATE: u32 = 22050;
+// Each audio packet after the first adds half a 256 sample block
+const SILENCE_PACKETS: u64 = 44;
+const SILENCE_PACKET_SAMPLES: u64 = 128;
+const OGG_SERIAL: u32 = 0x5eed;
+
+// About a quarter second of mono Ogg Vorbis silence. The setup header has one of
+// everything and as little of it as the format allows, and every audio packet says
+// its floor is unused, which decodes to zeros without touching the rest.
+fn silent_ogg() -> Vec<u8> {
+    let mut identification = b"\x01vorbis".to_vec();
+    // Version 0, mono
+    identification.extend_from_slice(&[0, 0, 0, 0, 1]);
+    identification.extend_from_slice(&SILENCE_RATE.to_le_bytes());
+    // No bitrate hints, both block sizes 256, framing bit
+    identification.extend_from_slice(&[0; 12]);
+    identification.extend_from_slice(&[0x88, 1]);
+
+    let vendor = b"placeholders.rs";
+    let mut comment = b"\x03vorbis".to_vec();
+    comment.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    comment.extend_from_slice(vendor);
+    // No user comments, framing bit
+    comment.extend_from_slice(&[0, 0, 0, 0, 1]);
+
+    // (value, bits) in the order the decoder reads them
+    let fields: &[(u32, u32)] = &[
+        // One codebook: sync, one dimension, two entries of length one, no lookup
+        (0, 8), (0x564342, 24), (1, 16), (2, 24), (0, 1), (0, 1), (0, 5), (0, 5), (0, 4),
+        // One time transform, always zero
+        (0, 6), (0, 16),
+        // One type 1 floor: no partitions, multiplier 1, 7 range bits for 128 samples
+        (0, 6), (1, 16), (0, 5), (0, 2), (7, 4),
+        // One type 0 residue covering nothing, classbook 0, no cascade
+        (0, 6), (0, 16), (0, 24), (0, 24), (0, 24), (0, 6), (0, 8), (0, 3), (0, 1),
+        // One type 0 mapping: one submap, no coupling, floor 0 and residue 0
+        (0, 6), (0, 16), (0, 1), (0, 1), (0, 2), (0, 8), (0, 8), (0, 8),
+        // One mode: short blocks, mapping 0
+        (0, 6), (0, 1), (0, 16), (0, 16), (0, 8),
+        // Framing bit
+        (1, 1),
+    ];
+    let mut setup = b"\x05vorbis".to_vec();
+    setup.extend(pack_bits(fields));
+
+    // Audio packet type 0, then the floor's nonzero bit, off
+    let audio = vec![vec![0]; SILENCE_PACKETS as usize];
+    let samples = (SILENCE_PACKETS - 1) * SILENCE_PACKET_SAMPLES;
+
+    let mut ogg = Vec::new();
+    // Beginning of stream, then the other two headers, then the audio to the end of stream
+    ogg_page(&mut ogg, 0, 0x02, 0, &[identification]);
+    ogg_page(&mut ogg, 1, 0x00, 0, &[comment, setup]);
+    ogg_page(&mut ogg, 2, 0x04, samples, &audio);
+    ogg
+}
+
+// Least significant bit first, the way Vorbis reads them
+fn pack_bits(fields: &[(u32, u32)]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut used = 0;
+    for &(value, bits) in fields {
+        for bit in 0..bits {
+            if used % 8 == 0 {
+                bytes.push(0);
+            }
+            if (value >> bit) & 1 == 1 {
+                *bytes.last_mut().unwrap() |= 1 << (used % 8);
+            }
+            used += 1;
+        }
+    }
+    bytes
+}
+
+fn ogg_page(ogg: &mut Vec<u8>, sequence: u32, flags: u8, granule: u64, packets: &[Vec<u8>]) {
+    // Each packet is laced into 255 byte segments and a shorter one to end it
+    let lacing: Vec<u8> = packets
+        .iter()
+        .flat_map(|packet| {
+            let full = packet.len() / 255;
+            std::iter::repeat_n(255, full).chain([(packet.len() - full * 255) as u8])
+        })
+        .collect();
+
+    let start = ogg.len();
+    ogg.extend_from_slice(b"OggS");
+    ogg.extend_from_slice(&[0, flags]);
+    ogg.extend_from_slice(&granule.to_le_bytes());
+    ogg.extend_from_slice(&OGG_SERIAL.to_le_bytes());
+    ogg.extend_from_slice(&sequence.to_le_bytes());
+    // Checksum, filled in below
+    ogg.extend_from_slice(&[0; 4]);
+    ogg.push(lacing.len() as u8);
+    ogg.extend_from_slice(&lacing);
+    for packet in packets {
+        ogg.extend_from_slice(packet);
+    }
+
+    let crc = ogg_crc(&ogg[start..]);
+    ogg[start + 22..start + 26].copy_from_slice(&crc.to_le_bytes());
+}
+
+// Ogg's CRC is the unreflected one, unlike PNG's
+fn ogg_crc(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0u32, |crc, &byte| {
+        (0..8).fold(crc ^ ((byte as u32) << 24), |crc, _| {
+            if crc & 0x8000_0000 != 0 { (crc << 1) ^ 0x04c1_1db7 } else { crc << 1 }
+        })
+    })
+}
+
+// Models
+
+fn protagonist_glb() -> Vec<u8> {
+    let mut glb = Glb::default();
+    let material = glb.material([0.8, 0.5, 0.2, 1.0]);
+
+    // Standing height with its bottom where the body collider's is
+    let mesh = glb.mesh(&Capsule3d::new(0.4, 1.0).into(), material);
+    let body = glb.node(json!({ "name": "Body", "mesh": mesh, "translation": [0.0, 0.9 - FEET_OFFSET, 0.0] }));
+    // Named like the real rig so root motion finds its bone
+    let hips = glb.node(json!({ "name": "Hips", "children": [body] }));
+    let armature = glb.node(json!({ "name": "Armature", "children": [hips] }));
+
+    // One still clip for every index the animation graph loads, named after `SCENES`
+    let times = glb.floats(&[0.0, 1.0], "SCALAR", true, None);
+    let rotations = glb.floats(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0], "VEC4", false, None);
+    for index in 0..=PROTAGONIST_ANIMATIONS {
+        let mut names: Vec<&str> = SCENES.iter().filter(|&(_, &i)| i == index).map(|(&name, _)| name).collect();
+        names.sort();
+        let name = names.first().map_or(format!("Placeholder {}", index), |name| name.to_string());
+        glb.animations.push(json!({
+            "name": name,
+            "samplers": [{ "input": times, "output": rotations, "interpolation": "LINEAR" }],
+            "channels": [{ "sampler": 0, "target": { "node": hips, "path": "rotation" } }],
+        }));
+    }
+
+    glb.into_bytes(&[armature])
+}
+
+fn starship_glb() -> Vec<u8> {
+    let mut glb = Glb::default();
+    let hull = glb.material([0.75, 0.75, 0.8, 1.0]);
+    let legs = glb.material([0.3, 0.3, 0.35, 1.0]);
+
+    // A tall hull on four legs, about the size of the real one
+    let mut parts = vec![(glb.mesh(&Cuboid::new(6.0, 14.0, 6.0).into(), hull), Vec3::Y * 9.0)];
+    let nose = glb.mesh(&Cuboid::new(3.0, 3.0, 3.0).into(), hull);
+    parts.push((nose, Vec3::Y * 17.5));
+    let leg = glb.mesh(&Cuboid::new(1.0, 4.0, 1.0).into(), legs);
+    for (x, z) in [(-3.5, -3.5), (-3.5, 3.5), (3.5, -3.5), (3.5, 3.5)] {
+        parts.push((leg, Vec3::new(x, 2.0, z)));
+    }
+
+    let children: Vec<usize> = parts
+        .into_iter()
+        .map(|(mesh, translation)| glb.node(json!({ "mesh": mesh, "translation": translation.to_array() })))
+        .collect();
+    let root = glb.node(json!({ "name": "Starship", "children": children }));
+    glb.into_bytes(&[root])
+}
+
+// Just enough of a glTF writer for the models above: one buffer, plain materials
+#[derive(Default)]
+struct Glb {
+    nodes: Vec<Value>,
+    meshes: Vec<Value>,
+    materials: Vec<Value>,
+    accessors: Vec<Value>,
+    buffer_views: Vec<Value>,
+    animations: Vec<Value>,
+    bin: Vec<u8>,
+}
+
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+impl Glb {
+    fn node(&mut self, node: Value) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    fn material(&mut self, color: [f32; 4]) -> usize {
+        self.materials.push(json!({
+            "pbrMetallicRoughness": { "baseColorFactor": color, "metallicFactor": 0.0, "roughnessFactor": 0.8 },
+        }));
+        self.materials.len() - 1
+    }
+
+    fn mesh(&mut self, mesh: &Mesh, material: usize) -> usize {
+        let positions = float3(mesh, Mesh::ATTRIBUTE_POSITION);
+        let normals = float3(mesh, Mesh::ATTRIBUTE_NORMAL);
+        let mut primitive = json!({
+            "attributes": {
+                // glTF wants the bounds on positions
+                "POSITION": self.floats(&positions, "VEC3", true, Some(ARRAY_BUFFER)),
+                "NORMAL": self.floats(&normals, "VEC3", false, Some(ARRAY_BUFFER)),
+            },
+            "material": material,
+        });
+        if let Some(indices) = mesh.indices() {
+            let indices: Vec<u32> = indices.iter().map(|index| index as u32).collect();
+            let bytes: Vec<u8> = indices.iter().flat_map(|index| index.to_le_bytes()).collect();
+            primitive["indices"] = self.accessor(&bytes, UNSIGNED_INT, indices.len(), "SCALAR", None, Some(ELEMENT_ARRAY_BUFFER)).into();
+        }
+
+        self.meshes.push(json!({ "primitives": [primitive] }));
+        self.meshes.len() - 1
+    }
+
+    fn floats(&mut self, values: &[f32], kind: &str, bounds: bool, target: Option<u32>) -> usize {
+        let components = match kind {
+            "SCALAR" => 1,
+            "VEC3" => 3,
+            _ => 4,
+        };
+        let bounds = bounds.then(|| {
+            let mut min = vec![f32::MAX; components];
+            let mut max = vec![f32::MIN; components];
+            for element in values.chunks(components) {
+                for (i, &value) in element.iter().enumerate() {
+                    min[i] = min[i].min(value);
+                    max[i] = max[i].max(value);
+                }
+            }
+            (min, max)
+        });
+        let bytes: Vec<u8> = values.iter().flat_map(|value| value.to_le_bytes()).collect();
+        self.accessor(&bytes, FLOAT, values.len() / components, kind, bounds, target)
+    }
+
+    fn accessor(
+        &mut self,
+        bytes: &[u8],
+        component_type: u32,
+        count: usize,
+        kind: &str,
+        bounds: Option<(Vec<f32>, Vec<f32>)>,
+        target: Option<u32>,
+    ) -> usize {
+        let mut view = json!({ "buffer": 0, "byteOffset": self.bin.len(), "byteLength": bytes.len() });
+        if let Some(target) = target {
+            view["target"] = target.into();
+        }
+        self.bin.extend_from_slice(bytes);
+        self.buffer_views.push(view);
+
+        let mut accessor = json!({
+            "bufferView": self.buffer_views.len() - 1,
+            "componentType": component_type,
+            "count": count,
+            "type": kind,
+        });
+        if let Some((min, max)) = bounds {
+            accessor["min"] = min.into();
+            accessor["max"] = max.into();
+        }
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn into_bytes(self, roots: &[usize]) -> Vec<u8> {
+        let mut root = json!({
+            "asset": { "version": "2.0", "generator": "placeholders.rs" },
+            "scene": 0,
+            "scenes": [{ "nodes": roots }],
+            "nodes": self.nodes,
+            "meshes": self.meshes,
+            "materials": self.materials,
+            "accessors": self.accessors,
+            "bufferViews": self.buffer_views,
+            "buffers": [{ "byteLength": self.bin.len() }],
+        });
+        if !self.animations.is_empty() {
+            root["animations"] = self.animations.into();
+        }
+
+        // Both chunks are padded to 4 bytes, the JSON with spaces
+        let mut json = serde_json::to_vec(&root).expect("glTF JSON serializes");
+        json.resize(json.len().next_multiple_of(4), b' ');
+        let mut bin = self.bin;
+        bin.resize(bin.len().next_multiple_of(4), 0);
+
+        let length = 12 + 8 + json.len() + 8 + bin.len();
+        let mut glb = Vec::with_capacity(length);
+        glb.extend_from_slice(b"glTF");
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&(length as u32).to_le_bytes());
+        for (kind, chunk) in [(b"JSON", json), (b"BIN\0", bin)] {
+            glb.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            glb.extend_from_slice(kind);
+            glb.extend_from_slice(&chunk);
+        }
+        glb
+    }
+}
+
+fn float3(mesh: &Mesh, attribute: MeshVertexAttribute) -> Vec<f32> {
+    match mesh.attribute(attribute) {
+        Some(VertexAttributeValues::Float32x3(values)) => values.iter().flatten().copied().collect(),
+        _ => Vec::new(),
+    }
+}
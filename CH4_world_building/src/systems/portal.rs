@@ -1,6 +1,11 @@
 use bevy::prelude::*;
 use avian3d::prelude::*;
 use crate::components::Protagonist;
+use crate::events::{GameEvent, PortalKind};
+
+// Where each portal sends the protagonist, relative to where it touched the sensor
+pub const TOP_PORTAL_JUMP: Vec3 = Vec3::new(0.0, -20.0, 0.0);
+pub const BOTTOM_PORTAL_JUMP: Vec3 = Vec3::new(5.0, 10.0, 0.0);
 
 // Marker components for the sensors
 #[derive(Component)]
@@ -14,6 +19,7 @@ pub fn portal_system(
     mut protagonist_query: Query<&mut Transform, With<Protagonist>>,
     top_sensor_query: Query<Entity, With<TopPortalSensor>>,
     bottom_sensor_query: Query<Entity, With<BottomPortalSensor>>,
+    mut game_events: EventWriter<GameEvent>,
 ) {
     for CollisionStarted(e1, e2) in collision_events.read() {
         let top_sensor = top_sensor_query.iter().next();
@@ -23,21 +29,22 @@ pub fn portal_system(
             // Check top sensor collision
             if let Some(top_entity) = top_sensor {
                 if *e1 == top_entity || *e2 == top_entity {
-                    info!("Protagonist collided with top portal sensor at y={}", transform.translation.y);
+                    dev_log!("Protagonist collided with top portal sensor at y={}", transform.translation.y);
                     // Teleport down into water
-                    transform.translation.y -= 20.0;
-                    info!("Teleported down to y={}", transform.translation.y);
+                    transform.translation += TOP_PORTAL_JUMP;
+                    dev_log!("Teleported down to y={}", transform.translation.y);
+                    game_events.send(GameEvent::PortalTraversed(PortalKind::Top));
                 }
             }
             
             // Check bottom sensor collision
             if let Some(bottom_entity) = bottom_sensor {
                 if *e1 == bottom_entity || *e2 == bottom_entity {
-                    info!("Protagonist collided with bottom portal sensor at y={}", transform.translation.y);
+                    dev_log!("Protagonist collided with bottom portal sensor at y={}", transform.translation.y);
                     // Teleport up and over
-                    transform.translation.x += 5.0;
-                    transform.translation.y += 10.0;
-                    info!("Teleported up to y={}", transform.translation.y);
+                    transform.translation += BOTTOM_PORTAL_JUMP;
+                    dev_log!("Teleported up to y={}", transform.translation.y);
+                    game_events.send(GameEvent::PortalTraversed(PortalKind::Bottom));
                 }
             }
         }
@@ -0,0 +1,233 @@
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    prelude::*,
+};
+use avian3d::prelude::*;
+use crate::resources::{CONCRETE_TEXTURE, CONTAINER_TEXTURE, ICE_TEXTURE, MARS_TEXTURE};
+
+use std::collections::{HashMap, HashSet};
+
+pub const MESH_COUNT: DiagnosticPath = DiagnosticPath::const_new("assets/meshes");
+pub const MATERIAL_COUNT: DiagnosticPath = DiagnosticPath::const_new("assets/materials");
+// Entities drawn with a mesh from the registry, which bevy can batch together
+pub const SHARED_PROP_COUNT: DiagnosticPath = DiagnosticPath::const_new("props/shared");
+
+/// Things the level spawns many of, each made once and shared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PropKind {
+    Container,
+    // The 90 by 90 floors
+    Slab,
+    // North and south walls
+    LongWall,
+    // East and west walls
+    ShortWall,
+    // Unit sphere, scaled to size
+    DustPuff,
+}
+
+impl PropKind {
+    pub const ALL: [PropKind; 5] = [
+        PropKind::Container,
+        PropKind::Slab,
+        PropKind::LongWall,
+        PropKind::ShortWall,
+        PropKind::DustPuff,
+    ];
+
+    /// Full size of the prop's bounding box
+    pub fn size(self) -> Vec3 {
+        match self {
+            PropKind::Container => Vec3::new(8.0, 3.0, 3.0),
+            PropKind::Slab => Vec3::new(90.0, 0.2, 90.0),
+            PropKind::LongWall => Vec3::new(100.0, 100.0, 20.0),
+            PropKind::ShortWall => Vec3::new(20.0, 95.0, 80.0),
+            PropKind::DustPuff => Vec3::splat(2.0),
+        }
+    }
+
+    // Dust is only for show, so it gets no collider
+    fn mesh_and_collider(self) -> (Mesh, Option<Collider>) {
+        let size = self.size();
+        match self {
+            PropKind::DustPuff => (Sphere::new(size.x / 2.0).into(), None),
+            _ => (Cuboid::from_size(size).into(), Some(Collider::cuboid(size.x, size.y, size.z))),
+        }
+    }
+
+    // What it looks like unless the spawn asks for something else
+    fn material(self) -> PropMaterial {
+        match self {
+            PropKind::Container => PropMaterial::ContainerMetal,
+            PropKind::Slab => PropMaterial::Mars,
+            PropKind::LongWall | PropKind::ShortWall => PropMaterial::Concrete,
+            PropKind::DustPuff => PropMaterial::Dust,
+        }
+    }
+}
+
+/// Materials shared between props, and with one-off meshes like the rocks and the plain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PropMaterial {
+    Mars,
+    Ice,
+    Concrete,
+    ContainerMetal,
+    Rock,
+    Dust,
+}
+
+impl PropMaterial {
+    pub const ALL: [PropMaterial; 6] = [
+        PropMaterial::Mars,
+        PropMaterial::Ice,
+        PropMaterial::Concrete,
+        PropMaterial::ContainerMetal,
+        PropMaterial::Rock,
+        PropMaterial::Dust,
+    ];
+
+    fn standard_material(self, asset_server: &AssetServer) -> StandardMaterial {
+        match self {
+            PropMaterial::Mars => StandardMaterial {
+                perceptual_roughness: 0.9,
+                metallic: 0.1,
+                base_color_texture: Some(asset_server.load(MARS_TEXTURE)),
+                ..default()
+            },
+            PropMaterial::Ice => StandardMaterial {
+                base_color_texture: Some(asset_server.load(ICE_TEXTURE)),
+                metallic: 1.0,
+                ..default()
+            },
+            PropMaterial::Concrete => StandardMaterial {
+                base_color_texture: Some(asset_server.load(CONCRETE_TEXTURE)),
+                metallic: 1.0,
+                ..default()
+            },
+            PropMaterial::ContainerMetal => StandardMaterial {
+                base_color_texture: Some(asset_server.load(CONTAINER_TEXTURE)),
+                metallic: 1.0,
+                ..default()
+            },
+            PropMaterial::Rock => StandardMaterial {
+                base_color_texture: Some(asset_server.load(ICE_TEXTURE)),
+                perceptual_roughness: 0.6,
+                ..default()
+            },
+            PropMaterial::Dust => StandardMaterial {
+                base_color: Color::srgba(0.6, 0.55, 0.5, 0.6),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            },
+        }
+    }
+}
+
+pub struct Prop {
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<StandardMaterial>,
+    // `None` for props that don't collide
+    pub collider: Option<Collider>,
+}
+
+/// Every prop's mesh, material and collider, made once at startup. Spawning with the
+/// same handles means one copy of each asset, and lets bevy batch the draws.
+#[derive(Resource)]
+pub struct PropRegistry {
+    props: HashMap<PropKind, Prop>,
+    materials: HashMap<PropMaterial, Handle<StandardMaterial>>,
+    meshes: HashSet<AssetId<Mesh>>,
+}
+
+impl FromWorld for PropRegistry {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>().clone();
+        let mut material_assets = world.resource_mut::<Assets<StandardMaterial>>();
+        let materials: HashMap<PropMaterial, Handle<StandardMaterial>> = PropMaterial::ALL
+            .into_iter()
+            .map(|material| (material, material_assets.add(material.standard_material(&asset_server))))
+            .collect();
+
+        let mut mesh_assets = world.resource_mut::<Assets<Mesh>>();
+        let props: HashMap<PropKind, Prop> = PropKind::ALL
+            .into_iter()
+            .map(|kind| {
+                let (mesh, collider) = kind.mesh_and_collider();
+                let prop = Prop {
+                    mesh: mesh_assets.add(mesh),
+                    material: materials[&kind.material()].clone(),
+                    collider,
+                };
+                (kind, prop)
+            })
+            .collect();
+
+        Self {
+            meshes: props.values().map(|prop| prop.mesh.id()).collect(),
+            props,
+            materials,
+        }
+    }
+}
+
+impl PropRegistry {
+    pub fn get(&self, kind: PropKind) -> &Prop {
+        &self.props[&kind]
+    }
+
+    pub fn material(&self, material: PropMaterial) -> Handle<StandardMaterial> {
+        self.materials[&material].clone()
+    }
+
+    /// Collider and mesh for a `kind` at `transform`, add a `RigidBody` to make it solid.
+    /// Only for props with a collider, the rest are spawned from `get`.
+    pub fn bundle(&self, kind: PropKind, transform: Transform) -> (Collider, PbrBundle) {
+        self.bundle_with_material(kind, self.get(kind).material.clone(), transform)
+    }
+
+    pub fn bundle_with_material(
+        &self,
+        kind: PropKind,
+        material: Handle<StandardMaterial>,
+        transform: Transform,
+    ) -> (Collider, PbrBundle) {
+        let prop = self.get(kind);
+        let collider = prop.collider.clone().unwrap_or_else(|| panic!("{:?} props don't collide", kind));
+        (
+            collider,
+            PbrBundle {
+                mesh: prop.mesh.clone(),
+                material,
+                transform,
+                ..default()
+            },
+        )
+    }
+
+    fn is_shared(&self, mesh: &Handle<Mesh>) -> bool {
+        self.meshes.contains(&mesh.id())
+    }
+}
+
+pub fn register_asset_diagnostics(app: &mut App) {
+    app.register_diagnostic(Diagnostic::new(MESH_COUNT))
+        .register_diagnostic(Diagnostic::new(MATERIAL_COUNT))
+        .register_diagnostic(Diagnostic::new(SHARED_PROP_COUNT));
+}
+
+/// How many meshes and materials exist, and how many props share theirs
+pub fn measure_asset_counts(
+    mut diagnostics: Diagnostics,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+    registry: Res<PropRegistry>,
+    mesh_handles: Query<&Handle<Mesh>>,
+) {
+    diagnostics.add_measurement(&MESH_COUNT, || meshes.len() as f64);
+    diagnostics.add_measurement(&MATERIAL_COUNT, || materials.len() as f64);
+    diagnostics.add_measurement(&SHARED_PROP_COUNT, || {
+        mesh_handles.iter().filter(|mesh| registry.is_shared(mesh)).count() as f64
+    });
+}
@@ -0,0 +1,197 @@
+use bevy::prelude::*;
+use avian3d::prelude::*;
+use crate::components::Protagonist;
+use crate::systems::climb::ClimbState;
+use crate::systems::health::Dead;
+use crate::systems::controller::ControllerConfig;
+use crate::systems::stance::{Stance, PRONE_LENGTH};
+use crate::systems::swim::Swimming;
+
+// How far below the protagonist's collider we still count as standing on something
+pub const GROUND_CHECK_DISTANCE: f32 = 0.15;
+// The normal ray starts a little above the feet so it can't start inside the ground
+pub const NORMAL_RAY_HEIGHT: f32 = 0.5;
+// Slightly smaller than the body collider so walls don't count as ground
+pub const FOOTPRINT_SIZE: Vec3 = Vec3::new(0.9, 0.1, 0.9);
+// Anything steeper than this (degrees) can't be walked on, the protagonist slides off
+const MAX_WALKABLE_SLOPE: f32 = 40.0;
+// Extra pull down steep surfaces, on top of what gravity does to a dynamic body
+const SLIDE_ACCELERATION: f32 = 12.0;
+// Upward speed that means we just jumped, so don't glue the body to the slope
+const JUMP_SPEED_THRESHOLD: f32 = 1.0;
+// Ledges up to this high (floor seams, the ramp's lower lip) are stepped over
+const MAX_STEP_HEIGHT: f32 = 0.35;
+// How far ahead of the body to look for a step
+const STEP_PROBE_DISTANCE: f32 = 0.15;
+
+// Present while the protagonist is standing on a collider
+#[derive(Component)]
+pub struct Grounded {
+    // Surface normal under the protagonist, straight up on flat ground
+    pub normal: Vec3,
+    // Collider we're standing on, used to ride moving platforms
+    pub entity: Entity,
+}
+
+impl Grounded {
+    // Angle of the ground from horizontal, in degrees
+    pub fn slope(&self) -> f32 {
+        self.normal.angle_between(Vec3::Y).to_degrees()
+    }
+
+    pub fn is_walkable(&self) -> bool {
+        self.slope() <= MAX_WALKABLE_SLOPE
+    }
+}
+
+/// Casts the protagonist's footprint downwards and keeps `Grounded` up to date
+pub fn update_grounded(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    mut protagonist_query: Query<(Entity, &Transform, Option<&mut Grounded>), With<Protagonist>>,
+) {
+    if let Ok((entity, transform, grounded)) = protagonist_query.get_single_mut() {
+        let footprint = Collider::cuboid(FOOTPRINT_SIZE.x, FOOTPRINT_SIZE.y, FOOTPRINT_SIZE.z);
+        let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+
+        let hit = spatial_query.cast_shape(
+            &footprint,
+            transform.translation,
+            transform.rotation,
+            Dir3::NEG_Y,
+            GROUND_CHECK_DISTANCE,
+            true,
+            filter.clone(),
+        );
+
+        let ground = hit.map(|hit| {
+            // A ray gives us the surface normal in world space, the edge of a
+            // ledge can be under the footprint but not under the ray
+            let normal = spatial_query
+                .cast_ray(
+                    transform.translation + Vec3::Y * NORMAL_RAY_HEIGHT,
+                    Dir3::NEG_Y,
+                    NORMAL_RAY_HEIGHT + GROUND_CHECK_DISTANCE * 2.0,
+                    true,
+                    filter,
+                )
+                .map(|ray| ray.normal.normalize_or_zero())
+                .filter(|normal| normal.y > 0.0)
+                .unwrap_or(Vec3::Y);
+            (normal, hit.entity)
+        });
+
+        match (ground, grounded) {
+            (Some((normal, ground_entity)), Some(mut grounded)) => {
+                if grounded.normal != normal || grounded.entity != ground_entity {
+                    grounded.normal = normal;
+                    grounded.entity = ground_entity;
+                }
+            }
+            (Some((normal, ground_entity)), None) => {
+                commands.entity(entity).insert(Grounded { normal, entity: ground_entity });
+            }
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<Grounded>();
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+/// Bends the walking velocity to follow the ground, slides down anything too
+/// steep and steps up over small ledges
+#[allow(clippy::type_complexity)]
+pub fn follow_ground(
+    time: Res<Time>,
+    config: Res<ControllerConfig>,
+    spatial_query: SpatialQuery,
+    mut protagonist_query: Query<
+        (Entity, &mut Transform, &mut LinearVelocity, &Grounded, &Stance),
+        (With<Protagonist>, Without<Dead>, Without<ClimbState>, Without<Swimming>),
+    >,
+) {
+    let Ok((entity, mut transform, mut velocity, grounded, stance)) = protagonist_query.get_single_mut() else {
+        return;
+    };
+
+    let normal = grounded.normal;
+    let horizontal = velocity.0.with_y(0.0);
+
+    if !grounded.is_walkable() {
+        // Straight down the slope
+        let downhill = (Vec3::NEG_Y - normal * Vec3::NEG_Y.dot(normal)).normalize_or_zero();
+        let uphill_flat = (-downhill).with_y(0.0).normalize_or_zero();
+
+        // Walking can steer across the slope, but not up it
+        let climbing = horizontal.dot(uphill_flat);
+        if climbing > 0.0 {
+            velocity.0 -= uphill_flat * climbing;
+        }
+        velocity.0 += downhill * SLIDE_ACCELERATION * time.delta_seconds();
+        return;
+    }
+
+    // Standing still or just jumped, leave it to the physics
+    if horizontal.length_squared() < 0.01 || velocity.0.y > JUMP_SPEED_THRESHOLD {
+        return;
+    }
+
+    // Same speed, but along the ground instead of into it
+    let along_ground = (horizontal - normal * horizontal.dot(normal)).normalize_or_zero() * horizontal.length();
+    velocity.0 = along_ground;
+
+    let body = config.mode.collider(*stance);
+    step_up(&spatial_query, entity, &mut transform, horizontal, &body, time.delta_seconds());
+}
+
+// Lifts the body onto a low ledge right in front of it
+fn step_up(
+    spatial_query: &SpatialQuery,
+    entity: Entity,
+    transform: &mut Transform,
+    horizontal: Vec3,
+    body: &Collider,
+    dt: f32,
+) {
+    let Ok(direction) = Dir3::new(horizontal) else {
+        return;
+    };
+    let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+    let reach = horizontal.length() * dt + STEP_PROBE_DISTANCE;
+    // Just off the floor so the ground we're standing on doesn't count
+    let feet = transform.translation + Vec3::Y * 0.02;
+
+    let Some(blocker) = spatial_query.cast_shape(body, feet, transform.rotation, direction, reach, true, filter.clone()) else {
+        return;
+    };
+
+    // Shape hits give local normals, so a ray finds out what we ran into. Anything
+    // walkable is a slope for the ground following, not a step.
+    let is_slope = spatial_query
+        .cast_ray(feet, direction, reach + PRONE_LENGTH / 2.0, true, filter.clone())
+        .is_some_and(|hit| hit.normal.angle_between(Vec3::Y).to_degrees() <= MAX_WALKABLE_SLOPE);
+    if is_slope {
+        return;
+    }
+
+    // Still blocked at step height means it's a wall, not a step
+    let raised = feet + Vec3::Y * MAX_STEP_HEIGHT;
+    if spatial_query
+        .cast_shape(body, raised, transform.rotation, direction, reach, true, filter.clone())
+        .is_some()
+    {
+        return;
+    }
+
+    // Drop back down just past the lip to find its top
+    let over = raised + *direction * (blocker.time_of_impact + 0.05);
+    let Some(top) = spatial_query.cast_shape(body, over, transform.rotation, Dir3::NEG_Y, MAX_STEP_HEIGHT, true, filter) else {
+        return;
+    };
+
+    let step = MAX_STEP_HEIGHT - top.time_of_impact;
+    if step > 0.01 {
+        transform.translation.y += step + 0.02;
+    }
+}
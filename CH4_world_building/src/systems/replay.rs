@@ -0,0 +1,245 @@
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use avian3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::resources::WorldSeed;
+use crate::systems::actions::{Action, ActionState, ScriptedActions};
+use crate::systems::loading::GameState;
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Everything needed to play a run back: the world seed, the timestep and the held
+/// actions for every frame. Saved as RON with `--record <file>`, played with `--replay <file>`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Recording {
+    pub seed: u64,
+    pub timestep: Duration,
+    // Recorded without a window. The headless world has no glTF scenes,
+    // so only replays in the same mode are expected to match.
+    pub headless: bool,
+    // Held actions in runs of identical frames, (frames, actions)
+    pub frames: Vec<(u32, Vec<Action>)>,
+    // State hash after the last frame, a replay that ends on a different one diverged
+    pub final_hash: Option<u64>,
+}
+
+impl Recording {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|error| format!("Can't read {}: {}", path, error))?;
+        ron::from_str(&text).map_err(|error| format!("Can't parse {}: {}", path, error))
+    }
+
+    pub fn save(&self, path: &PathBuf) -> Result<(), String> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())?;
+        std::fs::write(path, text).map_err(|error| format!("Can't write {}: {}", path.display(), error))
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.iter().map(|&(count, _)| count as usize).sum()
+    }
+
+    fn push(&mut self, held: Vec<Action>) {
+        match self.frames.last_mut() {
+            Some((count, last)) if *last == held => *count += 1,
+            _ => self.frames.push((1, held)),
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct Recorder {
+    path: PathBuf,
+    recording: Recording,
+}
+
+#[derive(Resource)]
+pub struct Replayer {
+    recording: Recording,
+    // Position in `recording.frames`
+    run: usize,
+    frame_in_run: u32,
+    frames_played: usize,
+    exit_when_done: bool,
+    done: bool,
+}
+
+// Same seed and timestep for the whole run, and the time doesn't depend on the frame rate
+fn fix_timestep(app: &mut App, timestep: Duration) {
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep))
+        .insert_resource(Time::<Fixed>::from_duration(timestep));
+}
+
+/// Records the run to `path`, written when the app exits
+pub fn start_recording(app: &mut App, path: &str, timestep: Duration, headless: bool) {
+    let seed = app.world().resource::<WorldSeed>().0;
+    fix_timestep(app, timestep);
+    app.insert_resource(Recorder {
+        path: PathBuf::from(path),
+        recording: Recording {
+            seed,
+            timestep,
+            headless,
+            ..default()
+        },
+    });
+    info!("Recording to {} with seed {}", path, seed);
+}
+
+/// Plays `path` back in place of the keyboard. Headless runs exit when it's over,
+/// windowed ones hand the controls back.
+pub fn start_replay(app: &mut App, path: &str, headless: bool) -> Result<(), String> {
+    let recording = Recording::load(path)?;
+    if recording.headless != headless {
+        warn!(
+            "{} was recorded {}, the replay may not match",
+            path,
+            if recording.headless { "headless" } else { "with a window" }
+        );
+    }
+
+    app.insert_resource(WorldSeed(recording.seed));
+    fix_timestep(app, recording.timestep);
+    app.insert_resource(Replayer {
+        recording,
+        run: 0,
+        frame_in_run: 0,
+        frames_played: 0,
+        exit_when_done: headless,
+        done: false,
+    });
+    Ok(())
+}
+
+/// Hash of every rigid body's position, rotation and velocity, bit for bit
+pub fn state_hash<'a>(bodies: impl Iterator<Item = (&'a Transform, Option<&'a LinearVelocity>)>) -> u64 {
+    // Query order isn't something to rely on, so sort before hashing
+    let mut states: Vec<Vec<u32>> = bodies
+        .map(|(transform, velocity)| {
+            transform
+                .translation
+                .to_array()
+                .into_iter()
+                .chain(transform.rotation.to_array())
+                .chain(velocity.map_or(Vec3::ZERO, |velocity| velocity.0).to_array())
+                .map(f32::to_bits)
+                .collect()
+        })
+        .collect();
+    states.sort_unstable();
+
+    let mut hasher = DefaultHasher::new();
+    states.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Frames only count once loading is over, before that input does nothing and
+// loading takes however long it takes
+fn is_ready(state: &State<GameState>) -> bool {
+    *state.get() == GameState::Playing
+}
+
+/// Feeds the next recorded frame into `ScriptedActions`, and reports the state hash
+/// once the recording runs out
+pub fn replay_actions(
+    replayer: Option<ResMut<Replayer>>,
+    mut scripted: ResMut<ScriptedActions>,
+    state: Res<State<GameState>>,
+    bodies: Query<(&Transform, Option<&LinearVelocity>), With<RigidBody>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let Some(mut replayer) = replayer else {
+        return;
+    };
+    if replayer.done {
+        return;
+    }
+    if !is_ready(&state) {
+        scripted.0 = Some(default());
+        return;
+    }
+
+    // Skip past finished runs
+    while replayer
+        .recording
+        .frames
+        .get(replayer.run)
+        .is_some_and(|&(count, _)| replayer.frame_in_run >= count)
+    {
+        replayer.run += 1;
+        replayer.frame_in_run = 0;
+    }
+
+    if let Some((_, held)) = replayer.recording.frames.get(replayer.run) {
+        scripted.0 = Some(held.iter().copied().collect());
+        replayer.frame_in_run += 1;
+        replayer.frames_played += 1;
+        return;
+    }
+
+    // Out of frames, so the state is now what it was when the recording stopped
+    let hash = state_hash(bodies.iter());
+    let verdict = match replayer.recording.final_hash {
+        Some(recorded) if recorded == hash => "matches the recording".to_string(),
+        Some(recorded) => format!("DIVERGED, recording ended on {:016x}", recorded),
+        None => "recording has no hash to compare".to_string(),
+    };
+    info!(
+        "Replay finished after {} frames, state hash {:016x}: {}",
+        replayer.frames_played, hash, verdict
+    );
+
+    replayer.done = true;
+    scripted.0 = None;
+    if replayer.exit_when_done {
+        exit.send(if replayer.recording.final_hash.is_some_and(|recorded| recorded != hash) {
+            AppExit::from_code(1)
+        } else {
+            AppExit::Success
+        });
+    }
+}
+
+/// Adds this frame's held actions to the recording
+pub fn record_actions(
+    recorder: Option<ResMut<Recorder>>,
+    action_state: Res<ActionState>,
+    state: Res<State<GameState>>,
+) {
+    let Some(mut recorder) = recorder else {
+        return;
+    };
+    if !is_ready(&state) {
+        return;
+    }
+    let held = Action::ALL.into_iter().filter(|&action| action_state.pressed(action)).collect();
+    recorder.recording.push(held);
+}
+
+/// Writes the recording out with the final state hash when the app closes
+pub fn finish_recording(
+    recorder: Option<ResMut<Recorder>>,
+    mut exits: EventReader<AppExit>,
+    bodies: Query<(&Transform, Option<&LinearVelocity>), With<RigidBody>>,
+) {
+    let Some(mut recorder) = recorder else {
+        return;
+    };
+    if exits.read().last().is_none() {
+        return;
+    }
+
+    let hash = state_hash(bodies.iter());
+    recorder.recording.final_hash = Some(hash);
+    match recorder.recording.save(&recorder.path) {
+        Ok(()) => info!(
+            "Recorded {} frames to {}, state hash {:016x}",
+            recorder.recording.frame_count(),
+            recorder.path.display(),
+            hash
+        ),
+        Err(error) => error!("{}", error),
+    }
+}
@@ -0,0 +1,140 @@
+use bevy::{
+    prelude::*,
+    render::{mesh::PrimitiveTopology, render_asset::RenderAssetUsages},
+};
+use avian3d::prelude::*;
+use noise::{Fbm, NoiseFn, Perlin};
+use serde::Deserialize;
+use crate::level::read_ron_asset;
+
+use std::f32::consts::TAU;
+
+pub const ROCKS_CONFIG_PATH: &str = "config/rocks.ron";
+
+/// A rock is a polygon extruded upwards in rings, each ring pushed in and out by noise
+#[derive(Deserialize, Debug, Clone)]
+pub struct RockShape {
+    pub height: f32,
+    // At the base, before the noise
+    pub radius: f32,
+    // Corners around the outside
+    pub facets: usize,
+    // Layers from bottom to top, more gives a more uneven silhouette
+    pub rings: usize,
+    // How far the noise pushes a corner, as a fraction of the radius
+    pub roughness: f32,
+    // Radius at the top as a fraction of the bottom's
+    pub taper: f32,
+}
+
+/// The kinds of rock the level scatters, read from `config/rocks.ron`
+#[derive(Resource, Deserialize, Debug, Clone)]
+pub struct RockConfig {
+    pub glacier: RockShape,
+    pub monolith: RockShape,
+}
+
+impl Default for RockConfig {
+    fn default() -> Self {
+        Self {
+            glacier: RockShape {
+                height: 18.0,
+                radius: 14.0,
+                facets: 11,
+                rings: 5,
+                roughness: 0.35,
+                taper: 0.55,
+            },
+            monolith: RockShape {
+                height: 60.0,
+                radius: 7.0,
+                facets: 7,
+                rings: 8,
+                roughness: 0.25,
+                taper: 0.6,
+            },
+        }
+    }
+}
+
+impl RockConfig {
+    /// Falls back to the built in shapes if the file is missing or broken
+    pub fn load(path: &str) -> Self {
+        read_ron_asset(path).unwrap_or_default()
+    }
+}
+
+/// A faceted mesh for `shape`, and a collider made of one convex hull per layer
+pub fn generate_rock(shape: &RockShape, seed: u64) -> (Mesh, Collider) {
+    let facets = shape.facets.max(3);
+    let rings = shape.rings.max(1);
+    let noise = Fbm::<Perlin>::new(seed as u32);
+
+    // Corners of every ring, bottom to top
+    let outline: Vec<Vec<Vec3>> = (0..=rings)
+        .map(|ring| {
+            let t = ring as f32 / rings as f32;
+            let y = t * shape.height;
+            let radius = shape.radius * (1.0 + (shape.taper - 1.0) * t);
+            (0..facets)
+                .map(|facet| {
+                    let angle = facet as f32 / facets as f32 * TAU;
+                    let direction = Vec3::new(angle.cos(), 0.0, angle.sin());
+                    // Sampled on a cylinder so neighbouring corners and rings move together
+                    let sample = direction * 1.5 + Vec3::Y * (y / shape.radius) * 0.5;
+                    let bump = noise.get(sample.as_dvec3().to_array()) as f32;
+                    direction * radius * (1.0 + bump * shape.roughness) + Vec3::Y * y
+                })
+                .collect()
+        })
+        .collect();
+    let top = outline[rings].iter().sum::<Vec3>() / facets as f32;
+    let bottom = outline[0].iter().sum::<Vec3>() / facets as f32;
+
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut uvs: Vec<[f32; 2]> = Vec::new();
+    // Wrapped around the sides, u across the facets and v up the height
+    let mut triangle = |corners: [(Vec3, f32); 3]| {
+        for (position, u) in corners {
+            positions.push(position.to_array());
+            uvs.push([u, position.y / shape.height]);
+        }
+    };
+
+    for ring in 0..rings {
+        for facet in 0..facets {
+            let next = (facet + 1) % facets;
+            let (u, next_u) = (facet as f32 / facets as f32, (facet + 1) as f32 / facets as f32);
+            let (a, b) = (outline[ring][facet], outline[ring][next]);
+            let (c, d) = (outline[ring + 1][facet], outline[ring + 1][next]);
+            triangle([(a, u), (c, u), (b, next_u)]);
+            triangle([(b, next_u), (c, u), (d, next_u)]);
+        }
+    }
+    for facet in 0..facets {
+        let next = (facet + 1) % facets;
+        let u = facet as f32 / facets as f32;
+        triangle([(top, u), (outline[rings][next], u), (outline[rings][facet], u)]);
+        triangle([(bottom, u), (outline[0][facet], u), (outline[0][next], u)]);
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.compute_flat_normals();
+
+    // Each layer between two rings is close enough to convex, and hulls are far cheaper
+    // to collide with than the triangles
+    let layers = (0..rings)
+        .filter_map(|ring| {
+            let mut points = [outline[ring].clone(), outline[ring + 1].clone()].concat();
+            if ring + 1 == rings {
+                points.push(top);
+            }
+            Collider::convex_hull(points)
+        })
+        .map(|hull| (Vec3::ZERO, Quat::IDENTITY, hull))
+        .collect();
+
+    (mesh, Collider::compound(layers))
+}
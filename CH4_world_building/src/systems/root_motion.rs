@@ -0,0 +1,332 @@
+use bevy::{
+    animation::{AnimationTarget, Interpolation, Keyframes, VariableCurve},
+    prelude::*,
+};
+use avian3d::prelude::*;
+use serde::Deserialize;
+use crate::components::Protagonist;
+use crate::level::read_ron_asset;
+use crate::resources::{Animations, SCENES};
+use crate::systems::climb::ClimbState;
+use crate::systems::health::Dead;
+use crate::systems::swim::Swimming;
+
+use std::collections::{hash_map::Entry, HashMap};
+
+pub const ROOT_MOTION_PATH: &str = "animations/root_motion.ron";
+
+// Clips whose root barely moves were exported "in place", they keep the velocity movement
+const MIN_ROOT_TRAVEL: f32 = 0.01;
+
+// What a clip takes from its root bone
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(default)]
+pub struct ClipRootMotion {
+    // Horizontal root travel drives the body's velocity
+    pub translation: bool,
+    // Root turning (yaw) turns the body
+    pub rotation: bool,
+}
+
+/// Root motion settings, read from `assets/animations/root_motion.ron`.
+/// Clips that aren't listed use the velocity-driven movement.
+#[derive(Resource, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RootMotionConfig {
+    pub enabled: bool,
+    // Bones whose name ends with this are the root, Mixamo calls it `mixamorig:Hips`
+    pub root_bone: String,
+    // Keyed by the names in `SCENES`
+    pub clips: HashMap<String, ClipRootMotion>,
+}
+
+impl Default for RootMotionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            root_bone: "Hips".to_string(),
+            clips: HashMap::new(),
+        }
+    }
+}
+
+impl RootMotionConfig {
+    pub fn load(path: &str) -> Self {
+        read_ron_asset(path).unwrap_or_default()
+    }
+
+    // Settings for an animation graph node, through its name in `SCENES`
+    fn for_node(&self, node: AnimationNodeIndex, animations: &Animations) -> Option<ClipRootMotion> {
+        let clip = animations.animations.iter().position(|&index| index == node)?;
+        SCENES
+            .iter()
+            .filter(|(_, index)| **index == clip)
+            .find_map(|(name, _)| self.clips.get(*name).copied())
+    }
+}
+
+// The root bone's keyframes from one clip, in the bone's parent space
+pub struct RootCurve {
+    translation: Option<(Vec<f32>, Vec<Vec3>)>,
+    rotation: Option<(Vec<f32>, Vec<Quat>)>,
+    duration: f32,
+}
+
+// Cubic spline keyframes are stored as (in tangent, value, out tangent)
+fn keyframe_values<T: Copy>(values: &[T], interpolation: &Interpolation) -> Vec<T> {
+    match interpolation {
+        Interpolation::CubicSpline => values.chunks(3).filter_map(|key| key.get(1).copied()).collect(),
+        _ => values.to_vec(),
+    }
+}
+
+// Index of the last keyframe at or before `time`, and how far it is to the next one
+fn locate(times: &[f32], time: f32) -> (usize, usize, f32) {
+    let next = times.partition_point(|&t| t <= time);
+    if next == 0 {
+        return (0, 0, 0.0);
+    }
+    if next >= times.len() {
+        let last = times.len() - 1;
+        return (last, last, 0.0);
+    }
+    let previous = next - 1;
+    let span = times[next] - times[previous];
+    let fraction = if span > 0.0 { (time - times[previous]) / span } else { 0.0 };
+    (previous, next, fraction)
+}
+
+impl RootCurve {
+    fn from_curves(curves: &[VariableCurve], duration: f32) -> Self {
+        let mut curve = Self {
+            translation: None,
+            rotation: None,
+            duration,
+        };
+
+        for variable_curve in curves {
+            let times = variable_curve.keyframe_timestamps.clone();
+            match &variable_curve.keyframes {
+                Keyframes::Translation(values) => {
+                    curve.translation = Some((times, keyframe_values(values, &variable_curve.interpolation)));
+                }
+                Keyframes::Rotation(values) => {
+                    curve.rotation = Some((times, keyframe_values(values, &variable_curve.interpolation)));
+                }
+                _ => {}
+            }
+        }
+
+        curve
+    }
+
+    pub fn translation_at(&self, time: f32) -> Vec3 {
+        let Some((times, values)) = &self.translation else {
+            return Vec3::ZERO;
+        };
+        let (a, b, fraction) = locate(times, time);
+        values[a].lerp(values[b], fraction)
+    }
+
+    pub fn rotation_at(&self, time: f32) -> Quat {
+        let Some((times, values)) = &self.rotation else {
+            return Quat::IDENTITY;
+        };
+        let (a, b, fraction) = locate(times, time);
+        values[a].slerp(values[b], fraction)
+    }
+
+    // Root travel and turn between two times, going round the end of the clip if it looped
+    fn delta(&self, from: f32, to: f32) -> (Vec3, Quat) {
+        if to >= from {
+            (
+                self.translation_at(to) - self.translation_at(from),
+                self.rotation_at(to) * self.rotation_at(from).inverse(),
+            )
+        } else {
+            let (end_travel, end_turn) = self.delta(from, self.duration);
+            let (start_travel, start_turn) = self.delta(0.0, to);
+            (end_travel + start_travel, start_turn * end_turn)
+        }
+    }
+
+    fn has_travel(&self) -> bool {
+        self.translation
+            .as_ref()
+            .is_some_and(|(_, values)| values.iter().any(|value| value.distance(values[0]) > MIN_ROOT_TRAVEL))
+    }
+}
+
+// Root curves pulled out of the clips so far, per animation graph node.
+// `None` means the clip has no root motion to use.
+#[derive(Resource, Default)]
+pub struct RootMotionClips {
+    curves: HashMap<AnimationNodeIndex, Option<RootCurve>>,
+}
+
+// Marks the skeleton's root bone. Remembers what's being taken out of its pose.
+#[derive(Component)]
+pub struct RootBone {
+    clip: Option<AnimationNodeIndex>,
+    settings: ClipRootMotion,
+    anchor_translation: Vec3,
+    anchor_rotation: Quat,
+    last_time: f32,
+}
+
+/// Finds the root bone once the protagonist's scene has spawned
+pub fn tag_root_bone(
+    mut commands: Commands,
+    config: Res<RootMotionConfig>,
+    targets: Query<(Entity, &Name), Added<AnimationTarget>>,
+) {
+    for (entity, name) in &targets {
+        if name.as_str().ends_with(config.root_bone.as_str()) {
+            dev_log!("Root motion bone: {}", name);
+            commands.entity(entity).insert(RootBone {
+                clip: None,
+                settings: ClipRootMotion::default(),
+                anchor_translation: Vec3::ZERO,
+                anchor_rotation: Quat::IDENTITY,
+                last_time: 0.0,
+            });
+        }
+    }
+}
+
+// The yaw part of a rotation given in the root bone's parent space, as seen in the world
+fn world_yaw(parent_rotation: Quat, local: Quat) -> f32 {
+    let world = parent_rotation * local * parent_rotation.inverse();
+    world.to_euler(EulerRot::YXZ).0
+}
+
+/// Moves the body by how far the root bone travelled in the current clip since last frame.
+/// Runs after the keyboard controls, so it replaces their velocity only for clips set up for it.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn apply_root_motion(
+    time: Res<Time>,
+    config: Res<RootMotionConfig>,
+    mut root_clips: ResMut<RootMotionClips>,
+    animations: Res<Animations>,
+    graphs: Res<Assets<AnimationGraph>>,
+    clips: Res<Assets<AnimationClip>>,
+    players: Query<(&AnimationPlayer, &AnimationTransitions)>,
+    mut bones: Query<(&AnimationTarget, &Parent, &mut RootBone)>,
+    parents: Query<&GlobalTransform>,
+    mut protagonist_query: Query<
+        (&mut Transform, &mut LinearVelocity),
+        (With<Protagonist>, Without<Dead>, Without<ClimbState>, Without<Swimming>),
+    >,
+) {
+    if !config.enabled {
+        return;
+    }
+    let Ok((target, parent, mut bone)) = bones.get_single_mut() else {
+        return;
+    };
+    let Ok((player, transitions)) = players.get(target.player) else {
+        return;
+    };
+    let Some(node) = transitions.get_main_animation() else {
+        return;
+    };
+    let Some(seek_time) = player.animation(node).map(|active| active.seek_time()) else {
+        return;
+    };
+
+    // Pull the root curve out of the clip the first time it plays
+    if let Entry::Vacant(entry) = root_clips.curves.entry(node) {
+        let Some(clip) = graphs
+            .get(&animations.graph)
+            .and_then(|graph| graph.get(node))
+            .and_then(|graph_node| graph_node.clip.as_ref())
+            .and_then(|handle| clips.get(handle))
+        else {
+            // Not loaded yet
+            return;
+        };
+        let curve = clip
+            .curves_for_target(target.id)
+            .map(|curves| RootCurve::from_curves(curves, clip.duration()));
+        entry.insert(curve);
+    }
+
+    let root_motion = match (config.for_node(node, &animations), root_clips.curves[&node].as_ref()) {
+        // An "in place" export has nothing to drive the body with
+        (Some(settings), Some(curve)) if !settings.translation || curve.has_travel() => Some((settings, curve)),
+        _ => None,
+    };
+    let Some((settings, curve)) = root_motion else {
+        // Velocity-driven clip
+        bone.clip = None;
+        return;
+    };
+
+    // New clip, start measuring from here
+    if bone.clip != Some(node) {
+        bone.clip = Some(node);
+        bone.settings = settings;
+        bone.anchor_translation = curve.translation_at(0.0);
+        bone.anchor_rotation = curve.rotation_at(0.0);
+        bone.last_time = seek_time;
+        return;
+    }
+
+    let (travel, turn) = curve.delta(bone.last_time, seek_time);
+    bone.last_time = seek_time;
+
+    let dt = time.delta_seconds();
+    let Ok(parent_transform) = parents.get(parent.get()) else {
+        return;
+    };
+    let Ok((mut transform, mut velocity)) = protagonist_query.get_single_mut() else {
+        return;
+    };
+
+    if settings.translation && dt > 0.0 {
+        // Parent space already includes the body's facing and the model's scale
+        let world_travel = parent_transform.affine().transform_vector3(travel);
+        velocity.0.x = world_travel.x / dt;
+        velocity.0.z = world_travel.z / dt;
+    }
+
+    if settings.rotation {
+        let (_, parent_rotation, _) = parent_transform.to_scale_rotation_translation();
+        let yaw = world_yaw(parent_rotation, turn);
+        transform.rotation = Quat::from_rotation_y(yaw) * transform.rotation;
+    }
+}
+
+/// Takes the travel and turn that went to the body back out of the root bone's pose,
+/// so the model doesn't walk away from its collider
+pub fn strip_root_motion(
+    mut bones: Query<(&Parent, &mut Transform, &RootBone)>,
+    parents: Query<&GlobalTransform>,
+) {
+    for (parent, mut transform, bone) in &mut bones {
+        if bone.clip.is_none() {
+            continue;
+        }
+        let Ok(parent_transform) = parents.get(parent.get()) else {
+            continue;
+        };
+        let (_, parent_rotation, _) = parent_transform.to_scale_rotation_translation();
+
+        if bone.settings.translation {
+            // Keep the height (bobbing, crouching) and drop the horizontal part
+            let offset = transform.translation - bone.anchor_translation;
+            let world_offset = parent_transform.affine().transform_vector3(offset);
+            let kept = parent_transform
+                .affine()
+                .inverse()
+                .transform_vector3(Vec3::Y * world_offset.y);
+            transform.translation = bone.anchor_translation + kept;
+        }
+
+        if bone.settings.rotation {
+            let yaw = world_yaw(parent_rotation, transform.rotation * bone.anchor_rotation.inverse());
+            transform.rotation =
+                parent_rotation.inverse() * Quat::from_rotation_y(-yaw) * parent_rotation * transform.rotation;
+        }
+    }
+}
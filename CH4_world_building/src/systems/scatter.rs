@@ -0,0 +1,328 @@
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+
+use std::f32::consts::{SQRT_2, TAU};
+
+// Tries around each sample before giving up on it, the usual number for Bridson's algorithm
+const POISSON_ATTEMPTS: usize = 30;
+
+/// Somewhere props mustn't go
+#[derive(Debug, Clone, Copy)]
+pub enum Exclusion {
+    Box { min: Vec3, max: Vec3 },
+    Sphere { center: Vec3, radius: f32 },
+}
+
+impl Exclusion {
+    pub fn from_box(center: Vec3, size: Vec3) -> Self {
+        Exclusion::Box {
+            min: center - size / 2.0,
+            max: center + size / 2.0,
+        }
+    }
+
+    // Whether a prop `radius` wide standing on `point` would be inside
+    fn blocks(&self, point: Vec3, radius: f32) -> bool {
+        match *self {
+            Exclusion::Box { min, max } => point.cmpge(min - radius).all() && point.cmple(max + radius).all(),
+            Exclusion::Sphere { center, radius: size } => point.distance(center) < size + radius,
+        }
+    }
+}
+
+/// Flat ground props can stand on, given by its top face
+#[derive(Debug, Clone, Copy)]
+pub enum ScatterSurface {
+    Rect { center: Vec3, half_size: Vec2 },
+    Disc { center: Vec3, radius: f32 },
+}
+
+impl ScatterSurface {
+    fn height_at(&self, point: Vec2) -> Option<f32> {
+        let (center, is_over) = match *self {
+            ScatterSurface::Rect { center, half_size } => {
+                (center, (point - center.xz()).abs().cmple(half_size).all())
+            }
+            ScatterSurface::Disc { center, radius } => (center, point.distance(center.xz()) <= radius),
+        };
+        is_over.then_some(center.y)
+    }
+}
+
+/// Where on the map a kind of prop is spread, looking down
+#[derive(Debug, Clone, Copy)]
+pub enum ScatterArea {
+    Rect { min: Vec2, max: Vec2 },
+    Ring { center: Vec2, min_radius: f32, max_radius: f32 },
+}
+
+impl ScatterArea {
+    fn bounds(&self) -> (Vec2, Vec2) {
+        match *self {
+            ScatterArea::Rect { min, max } => (min, max),
+            ScatterArea::Ring { center, max_radius, .. } => (center - max_radius, center + max_radius),
+        }
+    }
+
+    fn contains(&self, point: Vec2) -> bool {
+        match *self {
+            ScatterArea::Rect { min, max } => point.cmpge(min).all() && point.cmple(max).all(),
+            ScatterArea::Ring { center, min_radius, max_radius } => {
+                (min_radius..=max_radius).contains(&point.distance(center))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum RotationRule {
+    // Turned about Y only, so it stands the way it was modelled
+    Upright,
+    // Turned about Y and leaning over by up to this many radians
+    Tilted(f32),
+}
+
+impl RotationRule {
+    fn sample(self, rng: &mut impl Rng) -> Quat {
+        let yaw = Quat::from_rotation_y(rng.gen_range(0.0..TAU));
+        match self {
+            RotationRule::Upright => yaw,
+            RotationRule::Tilted(max_tilt) => {
+                let lean_direction = rng.gen_range(0.0..TAU);
+                let axis = Vec3::new(lean_direction.cos(), 0.0, lean_direction.sin());
+                Quat::from_axis_angle(axis, rng.gen_range(0.0..=max_tilt)) * yaw
+            }
+        }
+    }
+}
+
+/// How one kind of prop is spread about
+#[derive(Debug, Clone, Copy)]
+pub struct ScatterRule {
+    pub count: usize,
+    pub area: ScatterArea,
+    // Closest two of this kind can be, centre to centre
+    pub spacing: f32,
+    // Half the prop's width, kept clear of exclusions and other props
+    pub radius: f32,
+    // How far the origin sits above the ground, negative to sink it in
+    pub lift: f32,
+    pub rotation: RotationRule,
+}
+
+/// Places props kind by kind on Poisson disk samples, each kind kept off the ones
+/// placed before it
+pub struct Scatter<R: Rng> {
+    pub rng: R,
+    exclusions: Vec<Exclusion>,
+    surfaces: Vec<ScatterSurface>,
+    // Where each placed prop stands, and its radius
+    placed: Vec<(Vec3, f32)>,
+}
+
+impl<R: Rng> Scatter<R> {
+    pub fn new(rng: R, exclusions: Vec<Exclusion>, surfaces: Vec<ScatterSurface>) -> Self {
+        Self {
+            rng,
+            exclusions,
+            surfaces,
+            placed: Vec::new(),
+        }
+    }
+
+    // The highest surface under `point`
+    fn ground_at(&self, point: Vec2) -> Option<f32> {
+        self.surfaces
+            .iter()
+            .filter_map(|surface| surface.height_at(point))
+            .reduce(f32::max)
+    }
+
+    /// Transforms for up to `rule.count` props, fewer if there isn't room
+    pub fn place(&mut self, name: &str, rule: &ScatterRule) -> Vec<Transform> {
+        let mut candidates = poisson_disk(&mut self.rng, rule.area, rule.spacing);
+        // Samples grow out from the first one, so shuffle to not favour that corner
+        candidates.shuffle(&mut self.rng);
+
+        let mut transforms = Vec::new();
+        for point in candidates {
+            if transforms.len() == rule.count {
+                break;
+            }
+            let Some(ground) = self.ground_at(point) else {
+                continue;
+            };
+            let base = Vec3::new(point.x, ground, point.y);
+            if self.exclusions.iter().any(|exclusion| exclusion.blocks(base, rule.radius))
+                || self.placed.iter().any(|&(other, radius)| other.distance(base) < radius + rule.radius)
+            {
+                continue;
+            }
+
+            self.placed.push((base, rule.radius));
+            transforms.push(
+                Transform::from_translation(base + Vec3::Y * rule.lift)
+                    .with_rotation(rule.rotation.sample(&mut self.rng)),
+            );
+        }
+
+        if transforms.len() < rule.count {
+            warn!("Only found room for {} of {} {}", transforms.len(), rule.count, name);
+        }
+        transforms
+    }
+}
+
+/// Points in `area` no closer than `spacing` to each other, by Bridson's algorithm
+pub fn poisson_disk(rng: &mut impl Rng, area: ScatterArea, spacing: f32) -> Vec<Vec2> {
+    let (min, max) = area.bounds();
+    // Small enough that a cell holds at most one point
+    let cell = spacing / SQRT_2;
+    let columns = ((max.x - min.x) / cell).ceil() as usize + 1;
+    let rows = ((max.y - min.y) / cell).ceil() as usize + 1;
+    let cell_of = |point: Vec2| {
+        let cell = ((point - min) / cell).as_uvec2();
+        (cell.x as usize, cell.y as usize)
+    };
+
+    let mut grid: Vec<Option<usize>> = vec![None; columns * rows];
+    let mut points: Vec<Vec2> = Vec::new();
+    let mut active: Vec<usize> = Vec::new();
+
+    let first = Vec2::new(rng.gen_range(min.x..max.x), rng.gen_range(min.y..max.y));
+    let (x, y) = cell_of(first);
+    grid[y * columns + x] = Some(0);
+    points.push(first);
+    active.push(0);
+
+    while !active.is_empty() {
+        let slot = rng.gen_range(0..active.len());
+        let origin = points[active[slot]];
+
+        let mut found = None;
+        for _ in 0..POISSON_ATTEMPTS {
+            let angle = rng.gen_range(0.0..TAU);
+            let candidate = origin + Vec2::from_angle(angle) * rng.gen_range(spacing..spacing * 2.0);
+            if candidate.cmplt(min).any() || candidate.cmpgt(max).any() {
+                continue;
+            }
+
+            let (x, y) = cell_of(candidate);
+            let is_clear = (y.saturating_sub(2)..(y + 3).min(rows)).all(|row| {
+                (x.saturating_sub(2)..(x + 3).min(columns)).all(|column| {
+                    !grid[row * columns + column].is_some_and(|other| points[other].distance(candidate) < spacing)
+                })
+            });
+            if is_clear {
+                found = Some((candidate, y * columns + x));
+                break;
+            }
+        }
+
+        match found {
+            Some((point, cell)) => {
+                grid[cell] = Some(points.len());
+                active.push(points.len());
+                points.push(point);
+            }
+            None => {
+                active.swap_remove(slot);
+            }
+        }
+    }
+
+    points.retain(|&point| area.contains(point));
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    const AREA: ScatterArea = ScatterArea::Rect { min: Vec2::splat(-50.0), max: Vec2::splat(50.0) };
+    const GROUND: ScatterSurface = ScatterSurface::Rect { center: Vec3::ZERO, half_size: Vec2::splat(50.0) };
+
+    fn rule(count: usize) -> ScatterRule {
+        ScatterRule {
+            count,
+            area: AREA,
+            spacing: 6.0,
+            radius: 2.0,
+            lift: 0.0,
+            rotation: RotationRule::Upright,
+        }
+    }
+
+    #[test]
+    fn poisson_samples_keep_their_spacing() {
+        let points = poisson_disk(&mut StdRng::seed_from_u64(1), AREA, 5.0);
+
+        // Fills the area rather than stopping after a few
+        assert!(points.len() > 100, "only {} samples", points.len());
+        for (i, a) in points.iter().enumerate() {
+            assert!(AREA.contains(*a), "{a} is outside the area");
+            for b in &points[i + 1..] {
+                assert!(a.distance(*b) >= 5.0, "{a} and {b} are too close");
+            }
+        }
+    }
+
+    #[test]
+    fn ring_samples_stay_in_the_ring() {
+        let ring = ScatterArea::Ring { center: Vec2::new(10.0, -10.0), min_radius: 20.0, max_radius: 40.0 };
+        let points = poisson_disk(&mut StdRng::seed_from_u64(2), ring, 4.0);
+
+        assert!(!points.is_empty());
+        assert!(points.iter().all(|&point| ring.contains(point)));
+    }
+
+    #[test]
+    fn same_seed_same_placement() {
+        let place = |seed| {
+            let mut scatter = Scatter::new(StdRng::seed_from_u64(seed), Vec::new(), vec![GROUND]);
+            scatter.place("props", &rule(20))
+        };
+
+        assert_eq!(place(3), place(3));
+        assert_ne!(place(3), place(4));
+    }
+
+    #[test]
+    fn props_keep_out_of_exclusions_and_each_other() {
+        let exclusions = vec![
+            Exclusion::Sphere { center: Vec3::ZERO, radius: 15.0 },
+            Exclusion::from_box(Vec3::new(30.0, 0.0, 0.0), Vec3::new(10.0, 10.0, 100.0)),
+        ];
+        let mut scatter = Scatter::new(StdRng::seed_from_u64(5), exclusions, vec![GROUND]);
+        let rule = rule(40);
+        let placed: Vec<Vec3> = scatter.place("props", &rule).iter().map(|transform| transform.translation).collect();
+
+        assert!(!placed.is_empty());
+        for (i, &a) in placed.iter().enumerate() {
+            assert!(a.length() >= 15.0 + rule.radius, "{a} is inside the sphere");
+            assert!((a.x - 30.0).abs() >= 5.0 + rule.radius, "{a} is inside the box");
+            for &b in &placed[i + 1..] {
+                assert!(a.distance(b) >= rule.radius * 2.0, "{a} and {b} overlap");
+            }
+        }
+    }
+
+    #[test]
+    fn props_stand_on_the_highest_surface() {
+        // A raised platform over the left half, and nothing past x = 40
+        let surfaces = vec![
+            ScatterSurface::Rect { center: Vec3::new(-5.0, 0.0, 0.0), half_size: Vec2::new(45.0, 50.0) },
+            ScatterSurface::Rect { center: Vec3::new(-25.0, 3.0, 0.0), half_size: Vec2::new(25.0, 50.0) },
+        ];
+        let mut scatter = Scatter::new(StdRng::seed_from_u64(6), Vec::new(), surfaces);
+        let rule = ScatterRule { lift: 0.5, ..rule(60) };
+
+        for transform in scatter.place("props", &rule) {
+            let Vec3 { x, y, .. } = transform.translation;
+            assert!(x <= 40.0, "placed off the ground at x = {x}");
+            let ground = if x <= 0.0 { 3.0 } else { 0.0 };
+            assert_eq!(y, ground + rule.lift, "wrong height at x = {x}");
+        }
+    }
+}
@@ -1,6 +1,33 @@
-use crate::components::Protagonist;
-use crate::resources::Animations;
+use crate::components::{Protagonist, Starship};
+use crate::resources::{
+    Animations,
+    WorldSeed,
+    DIFFUSE_MAP,
+    ICE_TEXTURE_DARK,
+    PROTAGONIST_ANIMATIONS,
+    PROTAGONIST_GLB,
+    SPECULAR_MAP,
+    STARSHIP_GLB,
+    STARS_TEXTURE,
+    STAR_WELL_TEXTURE,
+};
 use crate::systems::portal::{TopPortalSensor, BottomPortalSensor};
+use crate::systems::input::FallingState;
+use crate::systems::health::{Health, RespawnPoint};
+use crate::systems::checkpoint::spawn_level_markers;
+use crate::systems::climb::Climbable;
+use crate::systems::stance::{NoiseEmitter, Stance};
+use crate::systems::environment::WaterVolume;
+use crate::systems::controller::{ControllerConfig, ControllerMode, KinematicController};
+use crate::systems::blend::{BlendSpaces, BLEND_SPACES_PATH};
+use crate::systems::markers::{AnimationMarkers, ANIMATION_EVENTS_PATH};
+use crate::systems::audio::Surface;
+use crate::systems::objectives::{Objectives, OBJECTIVES_PATH};
+use crate::systems::baked_colliders::BakedColliderHierarchy;
+use crate::systems::props::{PropKind, PropMaterial, PropRegistry};
+use crate::systems::rocks::{generate_rock, RockConfig};
+use crate::systems::scatter::{Exclusion, RotationRule, Scatter, ScatterArea, ScatterRule, ScatterSurface};
+use crate::level::{LevelData, LEVEL_PATH};
 
 use avian3d::prelude::*;
 use bevy::{
@@ -8,33 +35,81 @@ use bevy::{
     prelude::*,
 };
 
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+// Name, shape and centre of each wall
+const WALLS: [(&str, PropKind, Vec3); 4] = [
+    ("NorthWall", PropKind::LongWall, Vec3::new(0.0, -40.0, -50.0)),
+    ("SouthWall", PropKind::LongWall, Vec3::new(0.0, -40.0, 50.0)),
+    ("EastWall", PropKind::ShortWall, Vec3::new(40.0, -40.0, 0.0)),
+    ("WestWall", PropKind::ShortWall, Vec3::new(-40.0, -40.0, 0.0)),
+];
+
+// Walls with a flight of steps up the inside, the east wall has the ramp alongside instead
+const STEPPED_WALLS: [&str; 3] = ["NorthWall", "SouthWall", "WestWall"];
+// Each step is within grabbing height of the one below, and deep enough to stand on
+const STEP_RISE: f32 = 2.0;
+const STEP_DEPTH: f32 = 1.5;
+const STEP_WIDTH: f32 = 3.0;
 
+// Where the main floor slab sits, everything inside the walls stands on it
+const FLOOR_CENTER: Vec3 = Vec3::ZERO;
+
+// The Mars plain around the walls, a wide flat cylinder
+const TERRAIN_CENTER: Vec3 = Vec3::new(0.0, -90.0, 0.0);
+const TERRAIN_RADIUS: f32 = 1000.0;
+const TERRAIN_HALF_HEIGHT: f32 = 0.5;
+
+#[allow(clippy::too_many_arguments)]
 pub fn setup(
     mut commands: Commands, 
     asset_server: Res<AssetServer>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut graphs: ResMut<Assets<AnimationGraph>>,
+    controller_config: Res<ControllerConfig>,
+    world_seed: Res<WorldSeed>,
+    rock_config: Res<RockConfig>,
+    props: Res<PropRegistry>,
 ) {
 
+    // Level data: where the protagonist starts and the checkpoints
+    let level = LevelData::load(LEVEL_PATH);
+    let player_start = level.player_start.transform();
+    spawn_level_markers(&mut commands, &level);
+    commands.insert_resource(RespawnPoint(player_start));
+    if let Some(water) = level.water {
+        commands.insert_resource(WaterVolume {
+            min: Vec3::from_array(water.min),
+            max: Vec3::from_array(water.max),
+        });
+    }
+    commands.insert_resource(level);
+    commands.insert_resource(Objectives::load(OBJECTIVES_PATH));
+
     // Build the animation graph
     let mut graph = AnimationGraph::new();
-    const PROTAGONIST_ANIMATIONS: usize = 44;
-    let animations = graph
+    let flat_clips = graph.add_blend(1.0, graph.root);
+    let animations: Vec<_> = graph
         .add_clips(
             (0..=PROTAGONIST_ANIMATIONS)
-                .map(|i| GltfAssetLabel::Animation(i).from_asset("models/ProtagonistLowPoly/Protagonist.glb"))
+                .map(|i| GltfAssetLabel::Animation(i).from_asset(PROTAGONIST_GLB))
                 .map(|path| asset_server.load(path)), // Map to load the asset
             1.0,
-            graph.root,
+            flat_clips,
         )
         .collect();
 
+    // Locomotion blend spaces go next to the flat clips
+    let blend_spaces = BlendSpaces::build(BLEND_SPACES_PATH, &mut graph, &animations, &asset_server);
+    commands.insert_resource(blend_spaces);
+    commands.insert_resource(AnimationMarkers::load(ANIMATION_EVENTS_PATH, &asset_server));
+
     // Insert a resource with the current scene information
     let graph = graphs.add(graph);
     commands.insert_resource(Animations {
         animations,
+        flat_clips,
         graph: graph.clone(),
     });
 
@@ -47,10 +122,12 @@ pub fn setup(
             ..default()
         },
         EnvironmentMapLight {
-            diffuse_map: asset_server.load("environment_maps/pisa_diffuse_rgb9e5_zstd.ktx2"),
-            specular_map: asset_server.load("environment_maps/pisa_specular_rgb9e5_zstd.ktx2"),
+            diffuse_map: asset_server.load(DIFFUSE_MAP),
+            specular_map: asset_server.load(SPECULAR_MAP),
             intensity: 250.0,
-        }
+        },
+        // Spatial sounds are heard from the camera
+        SpatialListener::new(0.3),
     ));
 
     // Add Directional Lighting
@@ -76,58 +153,31 @@ pub fn setup(
     });
 
     // Static "floor"
-
-    
-    let floor_depth_scale = 0.9;
-    let max_floor_layer_count = 50.0;
-    let floor_material = materials.add(StandardMaterial {
-        perceptual_roughness: 0.9,
-        metallic: 0.1,
-        base_color_texture: Some(asset_server.load("textures/8k_mars.png")),
-        ..default()
-    });
-
-
     commands.spawn((
         RigidBody::Static,
-        Collider::cuboid(90.0, 0.2, 90.0),
-        PbrBundle {
-            mesh: meshes.add(Cuboid::new(90.0, 0.2, 90.0)),
-            material: floor_material,
-            ..default()
-        },
+        props.bundle(PropKind::Slab, Transform::from_translation(FLOOR_CENTER)),
         Name::new("Floor"),
     ));
 
     commands.spawn((
         RigidBody::Static,
-        Collider::cuboid(90.0, 0.2, 90.0),
-        PbrBundle {
-            mesh: meshes.add(Cuboid::new(90.0, 0.2, 90.0)),
-            material: materials.add(StandardMaterial {
-                base_color_texture: Some(asset_server.load("textures/ice_texture2.png")),
-                metallic: 1.0,
-                ..default()
-            }),
-            transform: Transform::from_xyz(0.0, -0.2, 0.0),
-            ..default()
-        },
+        props.bundle_with_material(
+            PropKind::Slab,
+            props.material(PropMaterial::Ice),
+            Transform::from_xyz(0.0, -0.2, 0.0),
+        ),
+        Surface::Ice,
         Name::new("SubFloor"),
     ));    
 
     commands.spawn((
         RigidBody::Static,
-        Collider::cuboid(90.0, 0.2, 90.0),
-        PbrBundle {
-            mesh: meshes.add(Cuboid::new(90.0, 0.2, 90.0)),
-            material: materials.add(StandardMaterial {
-                base_color_texture: Some(asset_server.load("textures/ice_texture2.png")),
-                metallic: 1.0,
-                ..default()
-            }),
-            transform: Transform::from_xyz(0.0, -80.0, 0.0),
-            ..default()
-        },
+        props.bundle_with_material(
+            PropKind::Slab,
+            props.material(PropMaterial::Ice),
+            Transform::from_xyz(0.0, -80.0, 0.0),
+        ),
+        Surface::Ice,
         Name::new("AquifierFloor"),
     ));
 
@@ -137,7 +187,7 @@ pub fn setup(
         PbrBundle {
             mesh: meshes.add(Extrusion::new(Annulus::new(5.0, 10.0), 10.0)),
             material: materials.add(StandardMaterial {
-                base_color_texture: Some(asset_server.load("textures/ice_texture3.png")),
+                base_color_texture: Some(asset_server.load(ICE_TEXTURE_DARK)),
                 metallic: 0.0,
                 ..default()
             }),
@@ -159,7 +209,7 @@ pub fn setup(
                 half_height: 0.1,
             }),
             material: materials.add(StandardMaterial {
-                base_color_texture: Some(asset_server.load("textures/star_well.png")),
+                base_color_texture: Some(asset_server.load(STAR_WELL_TEXTURE)),
                 base_color: Color::srgba(0.1, 0.1, 0.3, 0.9),
                 metallic: 0.0,
                 perceptual_roughness: 1.0,
@@ -183,7 +233,7 @@ pub fn setup(
                 half_height: 0.1,
             }),
             material: materials.add(StandardMaterial {
-                base_color_texture: Some(asset_server.load("textures/star_well.png")),
+                base_color_texture: Some(asset_server.load(STAR_WELL_TEXTURE)),
                 base_color: Color::srgba(0.3, 0.1, 0.1, 0.9),
                 metallic: 0.0,
                 perceptual_roughness: 1.0,
@@ -195,139 +245,47 @@ pub fn setup(
         },
     ));
 
-    // Starship
-
-    let mut rng_starship = rand::thread_rng(); // Random number generator
-
-    for _ in 0..1 {
-        // Generate a random position at least 30 units away from the center
-        let distance = rng_starship.gen_range(15.0..30.0); // Distance between 15 and 30 units
-        let angle = rng_starship.gen_range(0.0..std::f32::consts::TAU); // Random angle in radians
-        let x = distance * angle.cos();
-        let z = distance * angle.sin();
-    
-        // Generate a random rotation
-        let rotation = Quat::from_rotation_y(rng_starship.gen_range(0.0..std::f32::consts::TAU)); // Random rotation around the Y-axis
-    
-        // Spawn the entity
+    // Concrete walls around the floor
+    for (name, kind, center) in WALLS {
         commands.spawn((
-            SceneBundle {
-                scene: asset_server
-                    //.load(GltfAssetLabel::Scene(0).from_asset("models/industrial_building.glb")),
-                    .load(GltfAssetLabel::Scene(0).from_asset("models/starhopper.glb")),
-                transform: Transform::from_xyz(x, -2.0, z).with_rotation(rotation),
-                ..default()
-            },
-            // ColliderConstructorHierarchy::new(ColliderConstructor::ConvexHullFromMesh),
-            ColliderConstructorHierarchy::new(ColliderConstructor::TrimeshFromMesh),
             RigidBody::Static,
+            Climbable,
+            props.bundle(kind, Transform::from_translation(center)),
+            Name::new(name),
         ));
     }
 
-
-    let mut rng_glacier = rand::thread_rng(); // Random number generator
-
-    for _ in 0..3 {
-        // Generate a random position at least 30 units away from the center
-        let distance = rng_glacier.gen_range(30.0..50.0); // Distance between 30 and 50 units
-        let y = rng_glacier.gen_range(0.0..90.0) * -1.0; // Random height between 0 and 90 units
-        let angle = rng_glacier.gen_range(0.0..std::f32::consts::TAU); // Random angle in radians
-        let x = distance * angle.cos();
-        let z = distance * angle.sin();
-    
-        // Generate a random rotation
-        let rotation = Quat::from_rotation_y(rng_glacier.gen_range(0.0..std::f32::consts::TAU)); // Random rotation around the Y-axis
-    
-        // Spawn the entity
-        commands.spawn((
-            SceneBundle {
-                scene: asset_server
-                    .load(GltfAssetLabel::Scene(0).from_asset("python/Tall_Monolithic_Rock.glb")),
-                transform: Transform::from_xyz(x, y, z).with_rotation(rotation),
-                ..default()
-            },
-            // ColliderConstructorHierarchy::new(ColliderConstructor::ConvexHullFromMesh),
-            ColliderConstructorHierarchy::new(ColliderConstructor::TrimeshFromMesh),
-            RigidBody::Static,
-        ));
-    }    
-
-
-    // North wall
-    commands.spawn((
-        RigidBody::Static,
-        Collider::cuboid(100.0, 100.0, 20.0),
-        PbrBundle {
-            mesh: meshes.add(Cuboid::new(100.0, 100.0, 20.0)),
-            material: materials.add(StandardMaterial {
-                base_color_texture: Some(asset_server.load("textures/concrete.png")),
-                // base_color: Color::rgb(0.0, 0.0, 1.0), // Blue color
-                metallic: 1.0,
-                ..default()
-            }),
-            transform: Transform::from_xyz(0.0, -40.0, -50.0),
-            ..default()
-        },
-    ));
-
-    // South wall 
-    commands.spawn((
-        RigidBody::Static,
-        Collider::cuboid(100.0, 100.0, 20.0),
-        PbrBundle {
-            mesh: meshes.add(Cuboid::new(100.0, 100.0, 20.0)),
-            material: materials.add(StandardMaterial {
-                base_color_texture: Some(asset_server.load("textures/concrete.png")),
-                // base_color: Color::rgb(0.0, 0.0, 1.0), // Solid color
-                metallic: 1.0,
-                ..default()
-            }),
-            transform: Transform::from_xyz(0.0, -40.0, 50.0),
-            ..default()
-        },
-    ));
-
-    // East wall
-    commands.spawn((
-        RigidBody::Static,
-        Collider::cuboid(20.0, 95.0, 80.0),
-        PbrBundle {
-            mesh: meshes.add(Cuboid::new(20.0, 95.0, 80.0)),
-            material: materials.add(StandardMaterial {
-                // base_color: Color::rgb(0.0, 1.0, 1.0), // Green color
-                base_color_texture: Some(asset_server.load("textures/concrete.png")),
-                metallic: 1.0,
-                ..default()
-            }),
-            transform: Transform::from_xyz(40.0, -40.0, 0.0),
-            ..default()
-        },
-    ));
-
-    // West wall
-    commands.spawn((
-        RigidBody::Static,
-        Collider::cuboid(20.0, 95.0, 80.0),
-        PbrBundle {
-            mesh: meshes.add(Cuboid::new(20.0, 95.0, 80.0)),
-            material: materials.add(StandardMaterial {
-                base_color_texture: Some(asset_server.load("textures/concrete.png")),
-                metallic: 1.0,
-                ..default()
-            }),
-            transform: Transform::from_xyz(-40.0, -40.0, 0.0),
-            ..default()
-        },
-    ));
+    // Steps to climb the walls by, a ledge at a time
+    for (name, kind, center) in WALLS {
+        if !STEPPED_WALLS.contains(&name) {
+            continue;
+        }
+        for (step_center, size) in wall_steps(center, kind.size()) {
+            commands.spawn((
+                RigidBody::Static,
+                Climbable,
+                Collider::cuboid(size.x, size.y, size.z),
+                PbrBundle {
+                    mesh: meshes.add(Cuboid::from_size(size)),
+                    material: props.material(PropMaterial::Concrete),
+                    transform: Transform::from_translation(step_center),
+                    ..default()
+                },
+                Name::new(format!("{}Step", name)),
+            ));
+        }
+    }
 
     // Add ramp along west wall
     commands.spawn((
         RigidBody::Static,
+        Climbable,
+        Surface::Ice,
         Collider::cuboid(5.0, 1.0, 80.0),
         PbrBundle {
             mesh: meshes.add(Cuboid::new(5.0, 1.0, 80.0)),
             material: materials.add(StandardMaterial {
-                base_color_texture: Some(asset_server.load("textures/ice_texture3.png")),
+                base_color_texture: Some(asset_server.load(ICE_TEXTURE_DARK)),
                 metallic: 0.5,
                 perceptual_roughness: 0.7,
                 ..default()
@@ -341,23 +299,10 @@ pub fn setup(
 
     // GLTF Protagonist
 
-    commands.spawn((
-        RigidBody::Dynamic,
-        Collider::cuboid(1.0, 0.25, 1.0),
-        // AngularVelocity(Vec3::new(2.5, 3.5, 1.5)), 
-        ExternalImpulse::default(), // Add ExternalImpulse for jumping
-        Protagonist,                // Marker component for the Protagonist        
-        SceneBundle {       
-            scene: asset_server
-                .load(GltfAssetLabel::Scene(0)
-                .from_asset("models/ProtagonistLowPoly/Protagonist.glb")),
-            transform: Transform::from_xyz(0.0, 1.0, 0.0),
-            ..default()
-        },        
-    ));
+    spawn_protagonist(&mut commands, &asset_server, &controller_config, player_start);
 
     // Load the stars texture
-    let stars_texture_handle = asset_server.load("textures/8k_stars.png");
+    let stars_texture_handle = asset_server.load(STARS_TEXTURE);
     
     // Create a material with the stars texture
     let sky_material = materials.add(StandardMaterial {
@@ -383,68 +328,27 @@ pub fn setup(
     // Add large cylindrical terrain with Mars texture
     commands.spawn((
         RigidBody::Static,
-        Collider::cylinder(1000.0, 1.0),
+        Collider::cylinder(TERRAIN_RADIUS, TERRAIN_HALF_HEIGHT * 2.0),
         PbrBundle {
             mesh: meshes.add(Cylinder {
-                radius: 1000.0,
-                half_height: 0.5,
-            }),
-            material: materials.add(StandardMaterial {
-                base_color_texture: Some(asset_server.load("textures/8k_mars.png")),
-                perceptual_roughness: 0.9,
-                metallic: 0.1,
-                ..default()
+                radius: TERRAIN_RADIUS,
+                half_height: TERRAIN_HALF_HEIGHT,
             }),
-            transform: Transform::from_xyz(0.0, -90.0, 0.0),
+            material: props.material(PropMaterial::Mars),
+            transform: Transform::from_translation(TERRAIN_CENTER),
             ..default()
         },
     ));
 
-    // Random generator
-    let mut rng = rand::thread_rng();
-
-    // Metal shipping containers
-    let metal_texture_handle = asset_server.load("textures/container_metal.png");
-
-    // Create a material with the stars texture
-    let metal_material = materials.add(StandardMaterial {
-        base_color_texture: Some(metal_texture_handle),
-        metallic: 1.0,
-        ..default()
-    });
-
-    // Add random metal containers
-    for _ in 0..50 {
-        let random_position = Vec3::new(
-            rng.gen_range(-300.0..300.0),
-            rng.gen_range(0.0..50.0),
-            rng.gen_range(-300.0..300.0),
-        );
-
-        // Ensure the position isn't near the protagonist's start
-        if random_position.distance(Vec3::new(0.0, 1.0, 0.0)) > 50.0 {
-            commands.spawn((
-                RigidBody::Dynamic,
-                Collider::cuboid(8.0, 3.0, 3.0),
-                PbrBundle {
-                    mesh: meshes.add(Cuboid::new(8.0, 3.0, 3.0)),
-                    material: materials.add(StandardMaterial {
-                        base_color_texture: Some(asset_server.load("textures/container_metal.png")),
-                        metallic: 1.0,
-                        ..default()
-                    }),
-                    transform: Transform::from_translation(random_position)
-                        .with_rotation(Quat::from_euler(
-                            EulerRot::XYZ,
-                            rng.gen_range(0.0..std::f32::consts::PI),
-                            rng.gen_range(0.0..std::f32::consts::PI),
-                            rng.gen_range(0.0..std::f32::consts::PI),
-                        )),
-                    ..default()
-                },
-            ));
-        }
-    }
+    spawn_random_props(
+        &mut commands,
+        &asset_server,
+        &mut meshes,
+        &props,
+        &rock_config,
+        world_seed.0,
+        player_start.translation,
+    );
 
     // Add invisible floor
     commands.spawn((
@@ -453,4 +357,215 @@ pub fn setup(
         Name::new("InvisibleFloor"),
     )).insert(Transform::from_xyz(0.0, -5.2, 0.0));  // 5 units below SubFloor
 
-}
\ No newline at end of file
+}
+
+// Everything `spawn_random_props` put down, so it can be cleared for a new seed
+#[derive(Component)]
+pub struct RandomProp;
+
+/// A metal shipping container, free to be knocked about
+pub fn spawn_container(commands: &mut Commands, props: &PropRegistry, transform: Transform) -> Entity {
+    commands
+        .spawn((
+            RigidBody::Dynamic,
+            props.bundle(PropKind::Container, transform),
+            Surface::Metal,
+        ))
+        .id()
+}
+
+/// Centre and size of each step against the inner face of a wall, tallest and
+/// shallowest last, stopping one grab short of the top
+fn wall_steps(wall_center: Vec3, wall_size: Vec3) -> Vec<(Vec3, Vec3)> {
+    let floor_top = FLOOR_CENTER.y + PropKind::Slab.size().y / 2.0;
+    let wall_top = wall_center.y + wall_size.y / 2.0;
+    // Walls are thin along one axis, the inner face is on the side towards the middle
+    let (inward, thickness) = if wall_size.x < wall_size.z {
+        (Vec3::X * -wall_center.x.signum(), wall_size.x)
+    } else {
+        (Vec3::Z * -wall_center.z.signum(), wall_size.z)
+    };
+    let face = (wall_center + inward * thickness / 2.0).with_y(floor_top);
+    let count = ((wall_top - floor_top) / STEP_RISE).ceil() as usize - 1;
+
+    (1..=count)
+        .map(|step| {
+            let height = step as f32 * STEP_RISE;
+            let depth = (count + 1 - step) as f32 * STEP_DEPTH;
+            let size = if inward.x != 0.0 {
+                Vec3::new(depth, height, STEP_WIDTH)
+            } else {
+                Vec3::new(STEP_WIDTH, height, depth)
+            };
+            (face + inward * depth / 2.0 + Vec3::Y * height / 2.0, size)
+        })
+        .collect()
+}
+
+// Keep clear of the protagonist's start by this much
+const SPAWN_CLEARANCE: f32 = 20.0;
+
+// The walls and their steps, the ramp, the portal well and the start
+fn scatter_exclusions(player_start: Vec3) -> Vec<Exclusion> {
+    let mut exclusions: Vec<Exclusion> = WALLS
+        .iter()
+        .map(|&(_, kind, center)| Exclusion::from_box(center, kind.size()))
+        .collect();
+    for (name, kind, center) in WALLS {
+        if STEPPED_WALLS.contains(&name) {
+            exclusions.extend(wall_steps(center, kind.size()).into_iter().map(|(step, size)| Exclusion::from_box(step, size)));
+        }
+    }
+    // Tall enough to cover the ramp's slope
+    exclusions.push(Exclusion::from_box(Vec3::new(30.0, 0.0, 2.0), Vec3::new(5.0, 17.0, 80.0)));
+    exclusions.push(Exclusion::Sphere { center: Vec3::new(-10.0, -2.0, 10.0), radius: 11.0 });
+    exclusions.push(Exclusion::Sphere { center: player_start, radius: SPAWN_CLEARANCE });
+    exclusions
+}
+
+// The tops of the floor and the plain around it, from the same numbers they're spawned with
+fn scatter_surfaces() -> Vec<ScatterSurface> {
+    let slab = PropKind::Slab.size();
+    vec![
+        ScatterSurface::Rect { center: FLOOR_CENTER + Vec3::Y * slab.y / 2.0, half_size: slab.xz() / 2.0 },
+        ScatterSurface::Disc { center: TERRAIN_CENTER + Vec3::Y * TERRAIN_HALF_HEIGHT, radius: TERRAIN_RADIUS },
+    ]
+}
+
+/// The starship, the glacier rocks and the containers, placed from `seed`.
+/// Everything random in the level comes from here, so a replay gets the same world.
+pub fn spawn_random_props(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    meshes: &mut Assets<Mesh>,
+    props: &PropRegistry,
+    rock_config: &RockConfig,
+    seed: u64,
+    player_start: Vec3,
+) {
+    let mut scatter = Scatter::new(StdRng::seed_from_u64(seed), scatter_exclusions(player_start), scatter_surfaces());
+
+    // Starship, standing on the floor inside the walls
+    let starship_rule = ScatterRule {
+        count: 1,
+        area: ScatterArea::Ring { center: Vec2::ZERO, min_radius: 15.0, max_radius: 30.0 },
+        spacing: 10.0,
+        radius: 6.0,
+        // The model's origin is a little above its feet
+        lift: -2.0,
+        rotation: RotationRule::Upright,
+    };
+    for transform in scatter.place("starships", &starship_rule) {
+        commands.spawn((
+            RandomProp,
+            SceneBundle {
+                scene: asset_server
+                    //.load(GltfAssetLabel::Scene(0).from_asset("models/industrial_building.glb")),
+                    .load(GltfAssetLabel::Scene(0).from_asset(STARSHIP_GLB)),
+                transform,
+                ..default()
+            },
+            // Convex parts, baked on the first run and cached next to the model
+            BakedColliderHierarchy::new(STARSHIP_GLB),
+            RigidBody::Static,
+            Starship,
+            Climbable,
+            Surface::Metal,
+        ));
+    }
+
+    // Glaciers and monoliths out on the plain, each shaped from its own seed
+    let rock_material = props.material(PropMaterial::Rock);
+    let widest_rock = [&rock_config.glacier, &rock_config.monolith]
+        .map(|shape| shape.radius * (1.0 + shape.roughness))
+        .into_iter()
+        .fold(0.0, f32::max);
+    let rock_rule = ScatterRule {
+        count: 3,
+        area: ScatterArea::Ring { center: Vec2::ZERO, min_radius: 70.0, max_radius: 160.0 },
+        spacing: widest_rock * 2.0,
+        radius: widest_rock,
+        // Bedded in so the leaning ones don't float on one side
+        lift: -2.0,
+        rotation: RotationRule::Tilted(0.15),
+    };
+    for transform in scatter.place("rocks", &rock_rule) {
+        let shape = if scatter.rng.gen_bool(0.5) { &rock_config.glacier } else { &rock_config.monolith };
+        let (mesh, collider) = generate_rock(shape, scatter.rng.gen());
+
+        commands.spawn((
+            RandomProp,
+            PbrBundle {
+                mesh: meshes.add(mesh),
+                material: rock_material.clone(),
+                transform,
+                ..default()
+            },
+            collider,
+            RigidBody::Static,
+            Surface::Ice,
+            Name::new("Rock"),
+        ));
+    }
+
+    // Metal shipping containers, set down upright rather than dropped
+    let container_size = PropKind::Container.size();
+    let container_rule = ScatterRule {
+        count: 50,
+        area: ScatterArea::Rect { min: Vec2::splat(-300.0), max: Vec2::splat(300.0) },
+        spacing: 12.0,
+        radius: container_size.xz().length() / 2.0,
+        lift: container_size.y / 2.0 + 0.05,
+        rotation: RotationRule::Upright,
+    };
+    for transform in scatter.place("containers", &container_rule) {
+        let container = spawn_container(commands, props, transform);
+        commands.entity(container).insert(RandomProp);
+    }
+}
+
+/// Despawns the protagonist and brings it back fresh at `transform`
+pub fn reset_protagonist(
+    commands: &mut Commands,
+    protagonists: impl IntoIterator<Item = Entity>,
+    asset_server: &AssetServer,
+    config: &ControllerConfig,
+    transform: Transform,
+) {
+    for entity in protagonists {
+        commands.entity(entity).despawn_recursive();
+    }
+    spawn_protagonist(commands, asset_server, config, transform);
+}
+
+/// Spawns the protagonist, also used to bring it back on reset
+pub fn spawn_protagonist(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    config: &ControllerConfig,
+    transform: Transform,
+) {
+    let mut protagonist = commands.spawn((
+        config.mode.rigid_body(),
+        config.mode.collider(Stance::Standing),
+        Stance::Standing,
+        NoiseEmitter::default(),
+        // AngularVelocity(Vec3::new(2.5, 3.5, 1.5)), 
+        ExternalImpulse::default(), // Add ExternalImpulse for jumping
+        CollidingEntities::default(), // Needed for hazard damage
+        Protagonist,                // Marker component for the Protagonist        
+        Health::new(100.0),
+        FallingState::default(),
+        SceneBundle {       
+            scene: asset_server
+                .load(GltfAssetLabel::Scene(0)
+                .from_asset(PROTAGONIST_GLB)),
+            transform,
+            ..default()
+        },        
+    ));
+
+    if config.mode == ControllerMode::Kinematic {
+        protagonist.insert(KinematicController::default());
+    }
+}
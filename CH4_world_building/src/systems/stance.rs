@@ -0,0 +1,234 @@
+use bevy::{
+    animation::RepeatAnimation,
+    prelude::*,
+};
+use avian3d::prelude::*;
+use crate::components::Protagonist;
+use crate::resources::{Animations, SCENES};
+use crate::systems::actions::{Action, ActionState};
+use crate::systems::climb::ClimbState;
+use crate::systems::health::Dead;
+use crate::systems::swim::Swimming;
+use crate::systems::controller::ControllerConfig;
+use crate::systems::markers::{AnimationMarker, AnimationMarkerReached};
+
+use std::time::Duration;
+
+// The body collider's bottom sits this far below the protagonist's origin. Standing
+// the body is the flat box the level was built around, twice this tall.
+pub const FEET_OFFSET: f32 = 0.125;
+
+const STANDING_HEIGHT: f32 = 1.8;
+const CROUCHING_HEIGHT: f32 = 1.1;
+const PRONE_HEIGHT: f32 = 0.5;
+// Lying down takes up more room front to back
+pub const PRONE_LENGTH: f32 = 1.8;
+// The prone footprint is checked this far up, so the ground underneath doesn't count
+const FOOTPRINT_LIFT: f32 = 0.05;
+
+#[derive(Component, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stance {
+    #[default]
+    Standing,
+    Crouching,
+    Prone,
+}
+
+impl Stance {
+    pub fn height(self) -> f32 {
+        match self {
+            Stance::Standing => STANDING_HEIGHT,
+            Stance::Crouching => CROUCHING_HEIGHT,
+            Stance::Prone => PRONE_HEIGHT,
+        }
+    }
+
+    /// Body collider for this stance. Standing keeps the flat box the portals and floors
+    /// were tuned for, lower stances grow upwards from the same bottom.
+    pub fn collider(self) -> Collider {
+        if self == Stance::Standing {
+            return Collider::cuboid(1.0, FEET_OFFSET * 2.0, 1.0);
+        }
+
+        let height = self.height();
+        let length = if self == Stance::Prone { PRONE_LENGTH } else { 1.0 };
+
+        Collider::compound(vec![(
+            Vec3::Y * (height / 2.0 - FEET_OFFSET),
+            Quat::IDENTITY,
+            Collider::cuboid(1.0, height, length),
+        )])
+    }
+
+    pub fn speed_multiplier(self) -> f32 {
+        match self {
+            Stance::Standing => 1.0,
+            Stance::Crouching => 0.5,
+            Stance::Prone => 0.25,
+        }
+    }
+
+    // How loud moving around is compared to walking upright
+    pub fn noise_multiplier(self) -> f32 {
+        match self {
+            Stance::Standing => 1.0,
+            Stance::Crouching => 0.4,
+            Stance::Prone => 0.15,
+        }
+    }
+
+    pub fn forward_clip(self) -> &'static str {
+        match self {
+            Stance::Prone => "CRAWL",
+            _ => "LEFT_SHOULDER_ADVANCE",
+        }
+    }
+
+    pub fn backward_clip(self) -> &'static str {
+        match self {
+            Stance::Standing => "JOG_BACK",
+            Stance::Crouching => "WALK_BACK",
+            Stance::Prone => "CRAWL_BACKWARDS",
+        }
+    }
+}
+
+/// Pose to settle into when the protagonist stops moving
+pub fn play_rest_pose(
+    player: &mut AnimationPlayer,
+    transitions: &mut AnimationTransitions,
+    animations: &Animations,
+    stance: Stance,
+) {
+    if stance == Stance::Prone {
+        // No prone idle clip, so hold the crawl still
+        let crawl = *SCENES.get("CRAWL").unwrap();
+        transitions
+            .play(player, animations.animations[crawl], Duration::from_millis(250))
+            .set_speed(0.0);
+    } else {
+        let crouch = *SCENES.get("CROUCH").unwrap();
+        transitions
+            .play(player, animations.animations[crouch], Duration::from_millis(250))
+            .set_repeat(RepeatAnimation::Count(1));
+    }
+}
+
+// How loud a footstep and a landing are, upright, compared to walking
+const FOOTSTEP_LOUDNESS: f32 = 1.5;
+const LANDING_LOUDNESS: f32 = 2.5;
+// Noise per second a footstep fades by
+const FOOTSTEP_FADE: f32 = 4.0;
+
+// How much noise the protagonist is making right now, 1.0 is walking upright
+#[derive(Component, Default)]
+pub struct NoiseEmitter {
+    pub loudness: f32,
+    // Last footstep or landing, fading out
+    footstep: f32,
+}
+
+/// Crouch and prone toggles. Getting taller checks for a low ceiling first, and
+/// lying down checks there's room in front and behind.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn change_stance(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    config: Res<ControllerConfig>,
+    spatial_query: SpatialQuery,
+    mut protagonist_query: Query<
+        // The collider is swapped through commands, `SpatialQuery` already reads them all
+        (Entity, &Transform, &mut Stance, &Collider),
+        // No stances while swimming
+        (With<Protagonist>, Without<Dead>, Without<ClimbState>, Without<Swimming>),
+    >,
+    sensors: Query<(), With<Sensor>>,
+    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+    animations: Res<Animations>,
+) {
+    let Ok((entity, transform, mut stance, collider)) = protagonist_query.get_single_mut() else {
+        return;
+    };
+
+    let wanted = if actions.just_pressed(Action::Crouch) {
+        if *stance == Stance::Crouching { Stance::Standing } else { Stance::Crouching }
+    } else if actions.just_pressed(Action::Prone) {
+        if *stance == Stance::Prone { Stance::Standing } else { Stance::Prone }
+    } else {
+        return;
+    };
+
+    let extra_height = wanted.height() - stance.height();
+    if extra_height > 0.0 {
+        // Sweep the current body up by the height we'd gain, ignoring what we already touch
+        let blocked = spatial_query
+            .shape_hits(
+                collider,
+                transform.translation,
+                transform.rotation,
+                Dir3::Y,
+                extra_height,
+                8,
+                true,
+                SpatialQueryFilter::from_excluded_entities([entity]),
+            )
+            .iter()
+            .any(|hit| !sensors.contains(hit.entity));
+
+        if blocked {
+            dev_log!("Not enough room to go from {:?} to {:?}", *stance, wanted);
+            return;
+        }
+    }
+
+    if wanted == Stance::Prone {
+        let blocked = spatial_query
+            .shape_intersections(
+                &config.mode.collider(wanted),
+                transform.translation + Vec3::Y * FOOTPRINT_LIFT,
+                transform.rotation,
+                SpatialQueryFilter::from_excluded_entities([entity]),
+            )
+            .iter()
+            .any(|&hit| !sensors.contains(hit));
+
+        if blocked {
+            dev_log!("Not enough room to lie down");
+            return;
+        }
+    }
+
+    dev_log!("Stance {:?} -> {:?}", *stance, wanted);
+    *stance = wanted;
+    commands.entity(entity).insert(config.mode.collider(wanted));
+
+    if !actions.pressed(Action::Forward) && !actions.pressed(Action::Backward) {
+        for (mut player, mut transitions) in &mut animation_players {
+            play_rest_pose(&mut player, &mut transitions, &animations, wanted);
+        }
+    }
+}
+
+/// Steady noise from moving, with spikes on the footstep and landing markers
+pub fn update_noise(
+    time: Res<Time>,
+    mut marker_events: EventReader<AnimationMarkerReached>,
+    mut protagonist_query: Query<(&LinearVelocity, &Stance, &mut NoiseEmitter), With<Protagonist>>,
+) {
+    let spike = marker_events
+        .read()
+        .filter_map(|event| match event.marker {
+            AnimationMarker::Footstep => Some(FOOTSTEP_LOUDNESS),
+            AnimationMarker::Landing => Some(LANDING_LOUDNESS),
+            AnimationMarker::HandPlant => None,
+        })
+        .fold(0.0, f32::max);
+
+    for (velocity, stance, mut noise) in &mut protagonist_query {
+        noise.footstep = (noise.footstep - FOOTSTEP_FADE * time.delta_seconds()).max(spike * stance.noise_multiplier());
+
+        let speed = Vec2::new(velocity.0.x, velocity.0.z).length();
+        // Walking speed is 5, so walking upright makes a noise of 1
+        noise.loudness = (speed / 5.0 * stance.noise_multiplier()).max(noise.footstep);
+    }
+}
@@ -0,0 +1,176 @@
+use bevy::{
+    animation::RepeatAnimation,
+    prelude::*,
+};
+use avian3d::prelude::*;
+use crate::components::Protagonist;
+use crate::resources::{Animations, SCENES};
+use crate::systems::actions::{Action, ActionState};
+use crate::systems::climb::ClimbState;
+use crate::systems::controller::ControllerConfig;
+use crate::systems::environment::WaterVolume;
+use crate::systems::health::Dead;
+use crate::systems::protagonist::Grounded;
+use crate::systems::stance::{Stance, play_rest_pose};
+
+use std::f32::consts::FRAC_PI_3;
+use std::time::Duration;
+
+const TURN_SPEED: f32 = 2.0; // Radians per second, same as on land
+const SWIM_SPEED: f32 = 6.0;
+const FAST_SWIM_SPEED: f32 = 9.0;
+const BACKWARD_SWIM_FACTOR: f32 = 0.5;
+// Straight up or down when not swimming forward
+const VERTICAL_SWIM_SPEED: f32 = 4.0;
+// How quickly the swimmer gets up to speed, and how quickly the water slows them down
+const SWIM_ACCELERATION: f32 = 3.0;
+const WATER_DRAG: f32 = 1.5;
+// Pitch follows ascend/descend while swimming forward
+const MAX_PITCH: f32 = FRAC_PI_3;
+const PITCH_SPEED: f32 = 1.5;
+// Within this distance of the waterline the swimmer treads water instead of rising further
+const SURFACE_MARGIN: f32 = 0.6;
+// Below this pitch (radians) the swimmer is level enough to start walking on the floor
+const LEVEL_PITCH: f32 = 0.1;
+
+// Present while the protagonist is swimming, the walking controller ignores them until it's removed
+#[derive(Component, Default)]
+pub struct Swimming {
+    // Radians, positive is nose up
+    pub pitch: f32,
+    // Treading water at the waterline
+    pub surfaced: bool,
+}
+
+fn play_looping(player: &mut AnimationPlayer, transitions: &mut AnimationTransitions, animations: &Animations, clip: &str) {
+    let index = animations.animations[*SCENES.get(clip).unwrap()];
+    if transitions.get_main_animation() != Some(index) {
+        transitions
+            .play(player, index, Duration::from_millis(250))
+            .set_repeat(RepeatAnimation::Forever);
+        player.resume_all();
+    }
+}
+
+/// Starts swimming when entering the water, and hands back to walking when
+/// leaving it or settling on the aquifer floor
+#[allow(clippy::type_complexity)]
+pub fn update_swimming(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    water: Res<WaterVolume>,
+    config: Res<ControllerConfig>,
+    mut protagonist_query: Query<
+        (Entity, &Transform, &LinearVelocity, Option<&Swimming>, Has<Grounded>, &mut Stance, &mut Collider),
+        (With<Protagonist>, Without<Dead>, Without<ClimbState>),
+    >,
+    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+    animations: Res<Animations>,
+) {
+    let Ok((entity, transform, velocity, swimming, is_grounded, mut stance, mut collider)) = protagonist_query.get_single_mut() else {
+        return;
+    };
+
+    let in_water = water.contains(transform.translation);
+    let pushing_off = actions.pressed(Action::Ascend);
+
+    match swimming {
+        None if in_water && (!is_grounded || pushing_off) => {
+            dev_log!("Started swimming at {:?}", transform.translation);
+            commands.entity(entity).insert(Swimming::default());
+
+            // Always swim upright
+            if *stance != Stance::Standing {
+                *stance = Stance::Standing;
+                *collider = config.mode.collider(Stance::Standing);
+            }
+        }
+        Some(_) if !in_water => {
+            dev_log!("Left the water at {:?}", transform.translation);
+            commands.entity(entity).remove::<Swimming>();
+        }
+        Some(swimming) if is_grounded && !pushing_off && swimming.pitch.abs() < LEVEL_PITCH && velocity.0.y <= 0.1 => {
+            dev_log!("Walking on the aquifer floor");
+            commands.entity(entity).remove::<Swimming>();
+
+            // Ease out of the swim into the standing pose
+            for (mut player, mut transitions) in &mut animation_players {
+                play_rest_pose(&mut player, &mut transitions, &animations, *stance);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Pitch and yaw swimming along the body facing, with water drag and treading at the waterline
+#[allow(clippy::type_complexity)]
+pub fn swim_system(
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    water: Res<WaterVolume>,
+    mut swimmer_query: Query<
+        (&mut Swimming, &mut Transform, &mut LinearVelocity, &mut AngularVelocity, Has<Grounded>),
+        (With<Protagonist>, Without<Dead>),
+    >,
+    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+    animations: Res<Animations>,
+) {
+    let Ok((mut swimming, mut transform, mut velocity, mut angular_velocity, is_grounded)) = swimmer_query.get_single_mut() else {
+        return;
+    };
+    let dt = time.delta_seconds();
+
+    let axis = |positive: Action, negative: Action| {
+        actions.pressed(positive) as i32 as f32 - actions.pressed(negative) as i32 as f32
+    };
+    let turn = axis(Action::TurnLeft, Action::TurnRight);
+    let vertical = axis(Action::Ascend, Action::Descend);
+    let mut forward = axis(Action::Forward, Action::Backward);
+    if forward < 0.0 {
+        forward *= BACKWARD_SWIM_FACTOR;
+    }
+
+    let depth = water.surface_y() - transform.translation.y;
+    swimming.surfaced = depth < SURFACE_MARGIN;
+
+    // Nose up or down while swimming forward, level out otherwise. Can't dive
+    // nose-first into the floor or swim up out of the water.
+    let mut target_pitch = if forward > 0.0 { vertical * MAX_PITCH } else { 0.0 };
+    if swimming.surfaced {
+        target_pitch = target_pitch.min(0.0);
+    }
+    if is_grounded {
+        target_pitch = target_pitch.max(0.0);
+    }
+    let max_step = PITCH_SPEED * dt;
+    swimming.pitch += (target_pitch - swimming.pitch).clamp(-max_step, max_step);
+
+    let (yaw, _, _) = transform.rotation.to_euler(EulerRot::YXZ);
+    let yaw = yaw + turn * TURN_SPEED * dt;
+    transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, swimming.pitch, 0.0);
+    angular_velocity.0 = Vec3::ZERO;
+
+    let facing = transform.rotation * Vec3::NEG_Z;
+    let speed = if actions.pressed(Action::Run) { FAST_SWIM_SPEED } else { SWIM_SPEED };
+    let mut desired = facing * forward * speed;
+    if forward == 0.0 {
+        desired.y = vertical * VERTICAL_SWIM_SPEED;
+    }
+
+    if desired != Vec3::ZERO {
+        velocity.0 = velocity.0.lerp(desired, 1.0 - (-SWIM_ACCELERATION * dt).exp());
+    } else {
+        velocity.0 *= (-WATER_DRAG * dt).exp();
+    }
+
+    // The waterline holds the swimmer down, just below it
+    if swimming.surfaced && velocity.0.y > 0.0 {
+        velocity.0.y = 0.0;
+        transform.translation.y = transform.translation.y.min(water.surface_y() - SURFACE_MARGIN * 0.5);
+    }
+
+    let clip = if swimming.surfaced || forward <= 0.0 { "TREAD" } else { "SWIM" };
+    for (mut player, mut transitions) in &mut animation_players {
+        play_looping(&mut player, &mut transitions, &animations, clip);
+    }
+}